@@ -0,0 +1,14 @@
+# frozen_string_literal: true
+
+# Builds the `Pks` native extension (src/packs/ruby.rs) with rb-sys, e.g. from a
+# gemspec with `spec.extensions = ["ext/pks/extconf.rb"]`, then `require "pks"`.
+require "mkmf"
+require "rb_sys/mkmf"
+
+create_rust_makefile("pks/pks") do |r|
+  # Cargo.toml lives at the root of the repository
+  r.ext_dir = File.expand_path("../..", __dir__)
+  r.features = ["ruby"]
+  # The library is an rlib for the `pks` binary; Ruby needs a shared object
+  r.extra_rustc_args = ["--crate-type", "cdylib"]
+end
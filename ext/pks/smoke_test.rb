@@ -0,0 +1,41 @@
+# frozen_string_literal: true
+
+# Loads the extension built by extconf.rb and calls the read-only `Pks` functions
+# against tests/fixtures/simple_app. CI runs it after building the extension:
+#
+#   mkdir -p tmp/ext && cd tmp/ext
+#   ruby ../../ext/pks/extconf.rb && make
+#   ruby -I . ../../ext/pks/smoke_test.rb
+require "pks"
+
+def assert_equal(expected, actual, description)
+  return if expected == actual
+
+  abort "#{description}: expected #{expected.inspect}, got #{actual.inspect}"
+end
+
+root = File.expand_path("../../tests/fixtures/simple_app", __dir__)
+
+result = Pks.check(root)
+assert_equal 2, result["summary"]["violation_count"], "Pks.check violation count"
+assert_equal false, result["summary"]["success"], "Pks.check success"
+assert_equal ["packs/foo/app/services/foo.rb"],
+             result["violations"].map { |v| v["file"] }.uniq,
+             "Pks.check violation files"
+
+assert_equal [], Pks.validate(root), "Pks.validate"
+assert_equal "packs/foo", Pks.pack(root, "packs/foo")["name"], "Pks.pack"
+assert_equal "packs/foo",
+             Pks.pack_for_file(root, "packs/foo/app/services/foo.rb")["name"],
+             "Pks.pack_for_file"
+assert_equal true, Pks.packs(root).any? { |pack| pack["name"] == "packs/bar" }, "Pks.packs"
+assert_equal true, Pks.list_definitions(root).key?("::Bar"), "Pks.list_definitions"
+
+begin
+  Pks.pack(root, "packs/missing")
+  abort "Pks.pack: expected a RuntimeError for an unknown pack"
+rescue RuntimeError
+  # expected
+end
+
+puts "Pks smoke test passed"
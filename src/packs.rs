@@ -1,5 +1,31 @@
-// Currently there are no supported library APIs for packs. The public API is the CLI.
-// This may change in the future! Please file an issue if you have a use case for a library API.
+//! Library API for embedding pks in other Rust tools.
+//!
+//! Most of pks is only reachable through its CLI. The items re-exported here
+//! are the supported library surface: build a [`Configuration`] for an
+//! application, check it with [`check_all`], walk the resolved
+//! [`Reference`]s with [`all_references`], and look packs up through the
+//! configuration's [`PackSet`]. Everything else may change without notice.
+//!
+//! ```
+//! use packs::packs;
+//!
+//! let configuration =
+//!     packs::configuration("tests/fixtures/simple_app".into())?;
+//!
+//! let result = packs::check_all(&configuration, vec![])?;
+//! for violation in &result.reportable_violations {
+//!     println!(
+//!         "{}: {} references {}",
+//!         violation.identifier.file,
+//!         violation.identifier.referencing_pack_name,
+//!         violation.identifier.constant_name
+//!     );
+//! }
+//!
+//! let foo = configuration.pack_set.for_pack("packs/foo")?;
+//! assert_eq!(foo.name, "packs/foo");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
 pub mod cli;
 
 // Module declarations
@@ -7,20 +33,33 @@ pub(crate) mod bin_locater;
 pub(crate) mod caching;
 pub(crate) mod checker;
 pub(crate) mod checker_configuration;
+pub(crate) mod checkstyle;
 pub(crate) mod configuration;
 pub(crate) mod constant_resolver;
 pub(crate) mod creator;
 pub(crate) mod csv;
 pub(crate) mod dependencies;
+pub(crate) mod git;
+pub(crate) mod graph;
 pub(crate) mod ignored;
 pub(crate) mod json;
+pub(crate) mod junit;
+pub(crate) mod lsp;
 pub(crate) mod monkey_patch_detection;
+pub(crate) mod mover;
 pub(crate) mod pack;
 pub(crate) mod parsing;
+pub(crate) mod public_api;
 pub(crate) mod raw_configuration;
+pub(crate) mod reference_query;
+#[cfg(feature = "ruby")]
+mod ruby;
+pub(crate) mod sarif;
+pub(crate) mod stats;
 pub(crate) mod template;
 pub(crate) mod text;
 pub mod walk_directory;
+pub(crate) mod watch;
 
 mod constant_dependencies;
 mod file_utils;
@@ -32,28 +71,43 @@ mod reference_extractor;
 use crate::packs;
 use crate::packs::pack::write_pack_to_disk;
 
+// Public API
+pub use self::checker::reference::Reference;
+pub use self::checker::{
+    CheckAllResult, ParseError, SuppressedViolation, Violation,
+    ViolationIdentifier,
+};
+pub use self::checker_configuration::CheckerType;
+pub use self::pack::Pack;
+pub use self::pack_set::PackSet;
+pub use self::parsing::Suppression;
+pub use configuration::Configuration;
+
 // Internal imports
-pub(crate) use self::checker::Violation;
 use self::creator::CreateResult;
-pub(crate) use self::pack_set::PackSet;
 pub(crate) use self::parsing::process_files_with_cache;
 pub(crate) use self::parsing::ruby::experimental::get_experimental_constant_resolver;
 pub(crate) use self::parsing::ruby::zeitwerk::get_zeitwerk_constant_resolver;
+pub(crate) use self::parsing::ParseDiagnostic;
 pub(crate) use self::parsing::ParsedDefinition;
 pub(crate) use self::parsing::UnresolvedReference;
 use anyhow::bail;
+use cli::ColorBy;
 use cli::ColorChoice;
+use cli::GraphFormat;
 use cli::OutputFormat;
+use cli::ReferencesFormat;
+use cli::StatsFormat;
 use cli::ViolationsFound;
-pub(crate) use configuration::Configuration;
 pub(crate) use package_todo::PackageTodo;
 
 // External imports
 use anyhow::Context;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 pub fn greet() {
@@ -75,6 +129,22 @@ pub fn create(
     Ok(())
 }
 
+/// Writes a starting packwerk.yml and root package.yml. Runs before any
+/// configuration is loaded, since there may not be one yet.
+pub fn init(absolute_root: &Path) -> anyhow::Result<()> {
+    for (file, result) in creator::init(absolute_root)? {
+        match result {
+            CreateResult::AlreadyExists => {
+                println!("`{}` already exists!", file);
+            }
+            CreateResult::Success => {
+                println!("Successfully created `{}`!", file);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Determine whether to use colors based on the color choice
 fn color_mode_for(color: ColorChoice) -> text::ColorMode {
     match color {
@@ -98,31 +168,97 @@ pub fn check(
 ) -> anyhow::Result<()> {
     let result = checker::check_all(configuration, files)
         .context("Failed to check files")?;
+    report_check_result(&result, configuration, output_format, color)
+}
 
+/// Like `check`, but only for the files changed since the git ref `base`,
+/// reporting only the violations introduced since then.
+pub fn check_since(
+    configuration: &Configuration,
+    base: &str,
+    output_format: OutputFormat,
+    color: ColorChoice,
+    files: Vec<String>,
+) -> anyhow::Result<()> {
+    let result = checker::check_since(configuration, base, files)
+        .context("Failed to check files")?;
+    report_check_result(&result, configuration, output_format, color)
+}
+
+fn report_check_result(
+    result: &checker::CheckAllResult,
+    configuration: &Configuration,
+    output_format: OutputFormat,
+    color: ColorChoice,
+) -> anyhow::Result<()> {
+    write_check_result(result, configuration, output_format, color)?;
+
+    debug!("Finished writing check output");
+
+    if result.has_violations() || result.fails_on_parse_errors(configuration) {
+        return Err(ViolationsFound.into());
+    }
+
+    Ok(())
+}
+
+fn write_check_result(
+    result: &checker::CheckAllResult,
+    configuration: &Configuration,
+    output_format: OutputFormat,
+    color: ColorChoice,
+) -> anyhow::Result<()> {
     match output_format {
         OutputFormat::Packwerk => {
             text::write_text(
-                &result,
+                result,
                 configuration,
                 std::io::stdout(),
                 color_mode_for(color),
             )?;
         }
         OutputFormat::CSV => {
-            csv::write_csv(&result, configuration, std::io::stdout())?;
+            csv::write_csv(result, configuration, std::io::stdout())?;
         }
         OutputFormat::JSON => {
-            json::write_json(&result, configuration, std::io::stdout())?;
+            json::write_json(result, configuration, std::io::stdout())?;
+        }
+        OutputFormat::Sarif => {
+            sarif::write_sarif(result, configuration, std::io::stdout())?;
+        }
+        OutputFormat::Junit => {
+            junit::write_junit(result, configuration, std::io::stdout())?;
+        }
+        OutputFormat::Checkstyle => {
+            checkstyle::write_checkstyle(
+                result,
+                configuration,
+                std::io::stdout(),
+            )?;
         }
     }
+    Ok(())
+}
 
-    debug!("Finished writing check output");
-
-    if result.has_violations() {
-        return Err(ViolationsFound.into());
-    }
+pub fn watch(
+    configuration: Configuration,
+    output_format: OutputFormat,
+    color: ColorChoice,
+    files: Vec<String>,
+) -> anyhow::Result<()> {
+    watch::watch(configuration, files, |result, configuration, summary| {
+        if std::io::stdout().is_terminal() {
+            // Clear the screen so the pane always shows the latest run only
+            print!("\x1B[2J\x1B[H");
+        }
+        write_check_result(result, configuration, output_format, color)?;
+        println!("\n{}", summary);
+        Ok(())
+    })
+}
 
-    Ok(())
+pub fn lsp(configuration: Configuration) -> anyhow::Result<()> {
+    lsp::run(configuration)
 }
 
 pub fn update(configuration: &Configuration) -> anyhow::Result<()> {
@@ -174,6 +310,67 @@ pub fn add_dependency(
     Ok(())
 }
 
+pub fn move_to_pack(
+    configuration: &Configuration,
+    path: String,
+    destination: String,
+    color: ColorChoice,
+) -> anyhow::Result<()> {
+    let result = mover::move_to_pack(configuration, &path, &destination)?;
+    for (from, to) in &result.moved_files {
+        println!("Moved `{}` to `{}`", from, to);
+    }
+
+    if !result.resolved_violations.is_empty() {
+        println!(
+            "\n{} recorded violation(s) no longer occur and were removed from package_todo.yml:",
+            result.resolved_violations.len()
+        );
+        for identifier in &result.resolved_violations {
+            println!(
+                "- {} {} `{}`",
+                identifier.file,
+                identifier.violation_type,
+                identifier.constant_name
+            );
+        }
+    }
+
+    if result.new_violations.is_empty() {
+        println!("\nNo new violations.");
+    } else {
+        println!(
+            "\n{} new violation(s) after the move:",
+            result.new_violations.len()
+        );
+        for violation in &result.new_violations {
+            println!(
+                "{}\n",
+                text::format_violation_message(
+                    violation,
+                    configuration,
+                    color_mode_for(color)
+                )
+            );
+        }
+        println!(
+            "Add the missing dependencies, or run `pks update` to record them."
+        );
+    }
+    Ok(())
+}
+
+pub fn rename_pack(
+    configuration: &Configuration,
+    old: String,
+    new: String,
+) -> anyhow::Result<()> {
+    for (old, new) in mover::rename_pack(configuration, &old, &new)? {
+        println!("Renamed `{}` to `{}`", old, new);
+    }
+    Ok(())
+}
+
 pub fn list_included_files(configuration: Configuration) -> anyhow::Result<()> {
     configuration
         .included_files
@@ -182,15 +379,84 @@ pub fn list_included_files(configuration: Configuration) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn graph(
+    configuration: &Configuration,
+    format: GraphFormat,
+    with_references: bool,
+    color_by: ColorBy,
+) -> anyhow::Result<()> {
+    let graph = graph::build_graph(configuration, with_references)?;
+    println!("{}", graph.render(format, color_by)?.trim_end());
+    Ok(())
+}
+
+pub fn stats(
+    configuration: &Configuration,
+    format: StatsFormat,
+) -> anyhow::Result<()> {
+    let stats = stats::build_stats(configuration)?;
+    println!("{}", stats.render(format)?.trim_end());
+    Ok(())
+}
+
+pub fn references(
+    configuration: &Configuration,
+    filter: reference_query::ReferenceFilter,
+    format: ReferencesFormat,
+) -> anyhow::Result<()> {
+    let references = reference_query::query_references(configuration, &filter)?;
+    println!(
+        "{}",
+        reference_query::render(&references, format)?.trim_end()
+    );
+    Ok(())
+}
+
+pub fn why(
+    configuration: &Configuration,
+    from_pack: String,
+    to_pack: String,
+    format: ReferencesFormat,
+) -> anyhow::Result<()> {
+    let explanation = reference_query::explain_dependency(
+        configuration,
+        &from_pack,
+        &to_pack,
+    )?;
+    println!("{}", explanation.render(format)?.trim_end());
+    Ok(())
+}
+
 pub fn validate(configuration: &Configuration) -> anyhow::Result<()> {
     checker::validate_all(configuration)
 }
 
+/// Loads the configuration (packwerk.yml, packs and included files) of the
+/// application at `project_root`.
 pub fn configuration(project_root: PathBuf) -> anyhow::Result<Configuration> {
     let absolute_root = project_root.canonicalize()?;
     configuration::get(&absolute_root)
 }
 
+/// Checks `files` (or every included file, if empty) for violations, the way
+/// `pks check` does.
+pub fn check_all(
+    configuration: &Configuration,
+    files: Vec<String>,
+) -> anyhow::Result<CheckAllResult> {
+    checker::check_all(configuration, files)
+}
+
+/// Resolves the constant references in `files` (or every included file, if
+/// empty) to the packs that define them.
+pub fn all_references(
+    configuration: &Configuration,
+    files: Vec<String>,
+) -> anyhow::Result<Vec<Reference>> {
+    let absolute_paths = configuration.intersect_files(files);
+    reference_extractor::get_all_references(configuration, &absolute_paths)
+}
+
 pub fn check_unnecessary_dependencies(
     configuration: &Configuration,
     auto_correct: bool,
@@ -253,11 +519,103 @@ pub fn delete_cache(configuration: Configuration) {
     }
 }
 
+fn export_cache(
+    configuration: &Configuration,
+    archive: &Path,
+) -> anyhow::Result<()> {
+    let count =
+        caching::archive::export(&configuration.cache_directory, archive)?;
+    println!("Exported {} cache entries to {}", count, archive.display());
+    Ok(())
+}
+
+fn import_cache(
+    configuration: &Configuration,
+    archive: &Path,
+) -> anyhow::Result<()> {
+    let count = caching::archive::import(
+        &configuration.cache_directory,
+        archive,
+        &configuration.cache_fingerprint(),
+    )?;
+    println!(
+        "Imported {} cache entries from {}",
+        count,
+        archive.display()
+    );
+    Ok(())
+}
+
+fn cache_stats(configuration: &Configuration) -> anyhow::Result<()> {
+    let cache_directory = configuration
+        .cache_directory
+        .strip_prefix(&configuration.absolute_root)
+        .unwrap_or(&configuration.cache_directory);
+    let stats = caching::maintenance::stats(&configuration.cache_directory)?;
+    if stats.is_empty() {
+        println!("No cache in {}", cache_directory.display());
+        return Ok(());
+    }
+    println!("Cache directory: {}", cache_directory.display());
+    for directory in stats {
+        println!(
+            "\n{}: {} entries, {}",
+            directory.name,
+            directory.entries,
+            caching::maintenance::human_size(directory.bytes)
+        );
+        if let Some(last_run) = directory.last_run {
+            println!(
+                "  Last run: {} hits, {} misses ({:.1}% hit rate)",
+                last_run.hits,
+                last_run.misses,
+                last_run.hit_rate() * 100.0
+            );
+        }
+    }
+    Ok(())
+}
+
+fn verify_cache(configuration: &Configuration) -> anyhow::Result<()> {
+    let (checked, corrupt) =
+        caching::maintenance::verify(&configuration.cache_directory)?;
+    if corrupt.is_empty() {
+        println!("Verified {} cache entries, none are corrupt", checked);
+        return Ok(());
+    }
+    for entry in &corrupt {
+        println!("{}: {}", entry.path.display(), entry.error);
+    }
+    bail!(
+        "{} of {} cache entries are corrupt. pks ignores them and rewrites them the next time it checks their files, or run `pks delete-cache` to remove the cache.",
+        corrupt.len(),
+        checked
+    )
+}
+
+fn prune_cache(configuration: &Configuration) -> anyhow::Result<()> {
+    let removed = caching::maintenance::prune(configuration)?;
+    println!(
+        "Removed {} cache entries for files that are no longer included",
+        removed
+    );
+    Ok(())
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct ProcessedFile {
     pub absolute_path: PathBuf,
     pub unresolved_references: Vec<UnresolvedReference>,
     pub definitions: Vec<ParsedDefinition>,
+    // Left out when empty, so cache entries stay readable by packwerk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_errors: Vec<ParseDiagnostic>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+    /// `# pks:` comments that could not be read. Unlike parse errors, they
+    /// always fail a check.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invalid_suppressions: Vec<ParseDiagnostic>,
 }
 
 #[derive(
@@ -272,6 +630,53 @@ pub(crate) fn list_definitions(
     configuration: &Configuration,
     ambiguous: bool,
 ) -> anyhow::Result<()> {
+    for (name, relative_paths) in
+        constant_definitions(configuration, ambiguous)?
+    {
+        for relative_path in relative_paths {
+            println!("{:?} is defined at {:?}", name, relative_path);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn list_unused_public_api(
+    configuration: &Configuration,
+) -> anyhow::Result<()> {
+    let unused = public_api::unused_public_constants(configuration)?;
+    if unused.is_empty() {
+        println!("No unused public constants!");
+        return Ok(());
+    }
+
+    let mut current_pack = None;
+    for constant in &unused {
+        if current_pack != Some(&constant.pack_name) {
+            if current_pack.is_some() {
+                println!();
+            }
+            println!("{}", constant.pack_name);
+            current_pack = Some(&constant.pack_name);
+        }
+        println!(
+            "- {} ({})",
+            constant.constant_name,
+            constant.relative_path.display()
+        );
+    }
+    println!(
+        "\n{} public constant(s) are not referenced from any other pack",
+        unused.len()
+    );
+    Ok(())
+}
+
+/// Each constant with the files (relative to the root) that define it. With
+/// `ambiguous`, only constants with more than one definition.
+pub(crate) fn constant_definitions(
+    configuration: &Configuration,
+    ambiguous: bool,
+) -> anyhow::Result<HashMap<String, Vec<PathBuf>>> {
     let constant_resolver = if configuration.experimental_parser {
         let processed_files: Vec<ProcessedFile> = process_files_with_cache(
             &configuration.included_files,
@@ -297,20 +702,24 @@ pub(crate) fn list_definitions(
     let constant_definition_map = constant_resolver
         .fully_qualified_constant_name_to_constant_definition_map();
 
+    let mut definitions_by_name = HashMap::new();
     for (name, definitions) in constant_definition_map {
         if ambiguous && definitions.len() == 1 {
             continue;
         }
 
-        for definition in definitions {
-            let relative_path = definition
-                .absolute_path_of_definition
-                .strip_prefix(&configuration.absolute_root)?;
-
-            println!("{:?} is defined at {:?}", name, relative_path);
-        }
+        let relative_paths = definitions
+            .iter()
+            .map(|definition| {
+                definition
+                    .absolute_path_of_definition
+                    .strip_prefix(&configuration.absolute_root)
+                    .map(PathBuf::from)
+            })
+            .collect::<Result<Vec<PathBuf>, _>>()?;
+        definitions_by_name.insert(name.clone(), relative_paths);
     }
-    Ok(())
+    Ok(definitions_by_name)
 }
 
 fn expose_monkey_patches(
@@ -332,10 +741,18 @@ fn expose_monkey_patches(
 fn list_dependencies(
     configuration: &Configuration,
     pack_name: String,
+    direction: dependencies::Direction,
 ) -> anyhow::Result<()> {
-    println!("Pack dependencies for {}\n", pack_name);
+    match direction {
+        dependencies::Direction::Dependents => {
+            println!("Pack dependencies for {}\n", pack_name)
+        }
+        dependencies::Direction::Dependencies => {
+            println!("Packs that {} depends on\n", pack_name)
+        }
+    }
     let dependencies =
-        dependencies::find_dependencies(configuration, &pack_name)?;
+        dependencies::find_dependencies(configuration, &pack_name, direction)?;
     println!("Explicit ({}):", dependencies.explicit.len());
     if dependencies.explicit.is_empty() {
         println!("- None");
@@ -361,6 +778,63 @@ fn list_dependencies(
     Ok(())
 }
 
+fn list_transitive_dependencies(
+    configuration: &Configuration,
+    pack_name: String,
+    direction: dependencies::Direction,
+    depth: Option<usize>,
+) -> anyhow::Result<()> {
+    let tree = dependencies::find_transitive_dependencies(
+        configuration,
+        &pack_name,
+        direction,
+        depth,
+    )?;
+    match direction {
+        dependencies::Direction::Dependents => {
+            println!("Packs that transitively depend on {}\n", tree.name)
+        }
+        dependencies::Direction::Dependencies => {
+            println!("Packs that {} transitively depends on\n", tree.name)
+        }
+    }
+    if tree.children.is_empty() {
+        println!("- None");
+        return Ok(());
+    }
+    fn print_tree(tree: &dependencies::DependencyTree, indent: usize) {
+        for child in &tree.children {
+            let marker = if child.cycle {
+                " (cycle)"
+            } else if child.repeated {
+                " (see above)"
+            } else {
+                ""
+            };
+            println!("{}- {}{}", "  ".repeat(indent), child.name, marker);
+            print_tree(child, indent + 1);
+        }
+    }
+    print_tree(&tree, 0);
+    println!("\n{} pack(s)", tree.pack_count());
+    Ok(())
+}
+
+fn path(
+    configuration: &Configuration,
+    from_pack: String,
+    to_pack: String,
+) -> anyhow::Result<()> {
+    match dependencies::shortest_path(configuration, &from_pack, &to_pack)? {
+        Some(path) => println!("{}", path.join(" -> ")),
+        None => println!(
+            "{} does not depend on {}, directly or transitively",
+            from_pack, to_pack
+        ),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,177 @@
+//! Packing the cache directory into a single `.tar.gz` archive for
+//! `pks cache export` and `pks cache import`, e.g. to restore a warm cache in
+//! a later CI job.
+//!
+//! Entries are named by root-relative paths, so an archive built in one
+//! checkout is valid in any other checkout of the same code, as long as it was
+//! built with the same pks version and configuration (see
+//! `Configuration::cache_fingerprint`).
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use super::{
+    create_cache_dir_idempotently, remove_cache_entries, CACHE_ENTRIES,
+    FINGERPRINT_FILE_NAME,
+};
+
+/// Writes the pks entries in `cache_dir` to `archive_path`, returning the
+/// number of cache files written.
+pub(crate) fn export(
+    cache_dir: &Path,
+    archive_path: &Path,
+) -> anyhow::Result<usize> {
+    let fingerprint_path = cache_dir.join(FINGERPRINT_FILE_NAME);
+    if !fingerprint_path.exists() {
+        bail!(
+            "There is no cache to export in {}. Run `pks check` with `cache: true` first.",
+            cache_dir.display()
+        );
+    }
+
+    let file = File::create(archive_path)
+        .context(format!("Failed to create {}", archive_path.display()))?;
+    let mut builder =
+        tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    // The fingerprint goes first, so `import` can reject a mismatched
+    // archive before unpacking anything
+    builder.append_path_with_name(&fingerprint_path, FINGERPRINT_FILE_NAME)?;
+    let mut count = 0;
+    for entry in CACHE_ENTRIES {
+        let path = cache_dir.join(entry);
+        if path.is_dir() {
+            for file in files_under(&path)? {
+                let name = file.strip_prefix(cache_dir)?;
+                builder.append_path_with_name(&file, name)?;
+                count += 1;
+            }
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, entry)?;
+            count += 1;
+        }
+    }
+
+    builder
+        .into_inner()?
+        .finish()
+        .context(format!("Failed to write {}", archive_path.display()))?;
+    Ok(count)
+}
+
+/// Replaces the pks entries in `cache_dir` with the ones in `archive_path`,
+/// returning the number of cache files restored. Fails if the archive was
+/// built with a different `fingerprint`, since its entries would be discarded
+/// on the next run anyway.
+pub(crate) fn import(
+    cache_dir: &Path,
+    archive_path: &Path,
+    fingerprint: &str,
+) -> anyhow::Result<usize> {
+    let file = File::open(archive_path)
+        .context(format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = archive.entries().context(format!(
+        "{} is not a cache archive",
+        archive_path.display()
+    ))?;
+
+    let mut archived_fingerprint = String::new();
+    match entries.next() {
+        Some(entry) => {
+            let mut entry = entry?;
+            if entry.path()?.as_ref() != Path::new(FINGERPRINT_FILE_NAME) {
+                bail!(
+                    "{} is not a cache archive written by `pks cache export`",
+                    archive_path.display()
+                );
+            }
+            std::io::Read::read_to_string(
+                &mut entry,
+                &mut archived_fingerprint,
+            )?;
+        }
+        None => bail!("{} is empty", archive_path.display()),
+    }
+    if archived_fingerprint != fingerprint {
+        bail!(
+            "{} was built with a different pks version or configuration (`custom_associations`, `autoload_roots` or inflections), so its entries cannot be used",
+            archive_path.display()
+        );
+    }
+
+    // Without a fingerprint, a partly imported cache is discarded on the
+    // next run
+    remove_cache_entries(cache_dir);
+    let _ = std::fs::remove_file(cache_dir.join(FINGERPRINT_FILE_NAME));
+    create_cache_dir_idempotently(cache_dir);
+    let mut count = 0;
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let is_cache_entry = path
+            .components()
+            .next()
+            .and_then(|component| component.as_os_str().to_str())
+            .is_some_and(|name| CACHE_ENTRIES.contains(&name));
+        if !is_cache_entry {
+            bail!(
+                "Unexpected entry {} in {}",
+                path.display(),
+                archive_path.display()
+            );
+        }
+        // `unpack_in` refuses paths that would escape `cache_dir`
+        entry.unpack_in(cache_dir)?;
+        count += 1;
+    }
+    std::fs::write(cache_dir.join(FINGERPRINT_FILE_NAME), fingerprint)?;
+    Ok(count)
+}
+
+fn files_under(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(files_under(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let source = tempfile::TempDir::new()?;
+        let source_dir = source.path();
+        std::fs::create_dir_all(source_dir.join("zeitwerk"))?;
+        std::fs::write(source_dir.join("zeitwerk/abc"), "{}")?;
+        std::fs::write(source_dir.join("constant_resolver.json"), "{}")?;
+        std::fs::write(source_dir.join("packwerk_entry"), "{}")?;
+        std::fs::write(source_dir.join(FINGERPRINT_FILE_NAME), "fingerprint")?;
+
+        let archive_path = source_dir.join("cache.tar.gz");
+        assert_eq!(export(source_dir, &archive_path)?, 2);
+
+        let destination = tempfile::TempDir::new()?;
+        let destination_dir = destination.path().join("tmp/cache");
+        assert!(import(&destination_dir, &archive_path, "other").is_err());
+        assert!(!destination_dir.exists());
+
+        assert_eq!(import(&destination_dir, &archive_path, "fingerprint")?, 2);
+        assert!(destination_dir.join("zeitwerk/abc").exists());
+        assert!(destination_dir.join("constant_resolver.json").exists());
+        assert!(!destination_dir.join("packwerk_entry").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+//! `pks cache stats`, `pks cache verify` and `pks cache prune`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::per_file_cache::read_json_file;
+use super::resolved_references::{self, RESOLVED_REFERENCES_DIRECTORY};
+use super::{entry_file_name, LastRun, LAST_RUN_FILE_NAME};
+use crate::packs::Configuration;
+
+// Directories of per-file entries: parse results for each parser, and
+// resolved references
+const ENTRY_DIRECTORIES: [&str; 3] =
+    ["zeitwerk", "experimental", RESOLVED_REFERENCES_DIRECTORY];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DirectoryStats {
+    pub name: &'static str,
+    pub entries: usize,
+    pub bytes: u64,
+    pub last_run: Option<LastRun>,
+}
+
+impl LastRun {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Entry counts and sizes for each entry directory that exists.
+pub(crate) fn stats(
+    cache_directory: &Path,
+) -> anyhow::Result<Vec<DirectoryStats>> {
+    let mut stats = vec![];
+    for name in ENTRY_DIRECTORIES {
+        let directory = cache_directory.join(name);
+        if !directory.is_dir() {
+            continue;
+        }
+        let entries = entry_files(&directory)?;
+        let mut bytes = 0;
+        for entry in &entries {
+            bytes += std::fs::metadata(entry)?.len();
+        }
+        let last_run =
+            std::fs::read_to_string(directory.join(LAST_RUN_FILE_NAME))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+        stats.push(DirectoryStats {
+            name,
+            entries: entries.len(),
+            bytes,
+            last_run,
+        });
+    }
+    Ok(stats)
+}
+
+#[derive(Debug)]
+pub struct CorruptEntry {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Deserializes every entry, returning how many were checked and the ones
+/// that could not be read.
+pub(crate) fn verify(
+    cache_directory: &Path,
+) -> anyhow::Result<(usize, Vec<CorruptEntry>)> {
+    let mut checked = 0;
+    let mut corrupt = vec![];
+    for name in ENTRY_DIRECTORIES {
+        let directory = cache_directory.join(name);
+        if !directory.is_dir() {
+            continue;
+        }
+        for path in entry_files(&directory)? {
+            checked += 1;
+            let result = if name == RESOLVED_REFERENCES_DIRECTORY {
+                resolved_references::read_entry(&path).map(|_| ())
+            } else {
+                read_json_file(&path).map(|_| ())
+            };
+            if let Err(e) = result {
+                corrupt.push(CorruptEntry {
+                    path,
+                    error: format!("{:#}", e),
+                });
+            }
+        }
+    }
+    Ok((checked, corrupt))
+}
+
+/// Removes the entries that do not belong to any of the configuration's
+/// included files, e.g. because the file was deleted or is now excluded.
+/// Returns the number of entries removed.
+pub(crate) fn prune(configuration: &Configuration) -> anyhow::Result<usize> {
+    let expected: HashSet<String> = configuration
+        .included_files
+        .iter()
+        .map(|path| entry_file_name(&configuration.absolute_root, path))
+        .collect();
+
+    let mut removed = 0;
+    for name in ENTRY_DIRECTORIES {
+        let directory = configuration.cache_directory.join(name);
+        if !directory.is_dir() {
+            continue;
+        }
+        for path in entry_files(&directory)? {
+            let is_expected = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| expected.contains(name));
+            if !is_expected {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+// The per-file entries in an entry directory, sorted by name
+fn entry_files(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_last_run = path
+            .file_name()
+            .is_some_and(|name| name == LAST_RUN_FILE_NAME);
+        if path.is_file() && !is_last_run {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// e.g. "512 B" or "3.4 MB"
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(3 * 1024 + 512), "3.5 KB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_verify_reports_corrupt_entries() -> anyhow::Result<()> {
+        let cache_dir = tempfile::TempDir::new()?;
+        let zeitwerk = cache_dir.path().join("zeitwerk");
+        std::fs::create_dir_all(&zeitwerk)?;
+        std::fs::write(
+            zeitwerk.join("a"),
+            r#"{"file_contents_digest":"x","processed_file":{"absolute_path":"/a.rb","unresolved_references":[],"definitions":[]}}"#,
+        )?;
+        std::fs::write(zeitwerk.join("b"), r#"{"file_contents_digest":"#)?;
+        std::fs::write(
+            zeitwerk.join(LAST_RUN_FILE_NAME),
+            r#"{"hits":1,"misses":1}"#,
+        )?;
+
+        let (checked, corrupt) = verify(cache_dir.path())?;
+        assert_eq!(checked, 2);
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(corrupt[0].path, zeitwerk.join("b"));
+
+        let stats = stats(cache_dir.path())?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].entries, 2);
+        assert_eq!(stats[0].last_run, Some(LastRun { hits: 1, misses: 1 }));
+        Ok(())
+    }
+}
@@ -1,9 +1,16 @@
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
 use super::{file_utils::file_content_digest, ProcessedFile};
+pub(crate) mod archive;
 pub(crate) mod cache;
+pub(crate) mod maintenance;
 pub(crate) mod noop_cache;
 pub(crate) mod per_file_cache;
+pub(crate) mod resolved_references;
 
 pub enum CacheResult {
     Processed(ProcessedFile),
@@ -14,7 +21,9 @@ pub enum CacheResult {
 pub struct EmptyCacheEntry {
     #[allow(dead_code)]
     pub filepath: PathBuf,
+    /// Only computed when the file's metadata does not match its entry
     pub file_contents_digest: String,
+    pub file_metadata: Option<FileMetadata>,
     #[allow(dead_code)]
     pub file_name_digest: String,
     pub cache_file_path: PathBuf,
@@ -23,24 +32,185 @@ pub struct EmptyCacheEntry {
 impl EmptyCacheEntry {
     pub fn new(
         cache_directory: &Path,
+        absolute_root: &Path,
         filepath: &Path,
     ) -> anyhow::Result<EmptyCacheEntry> {
-        let file_digest = md5::compute(filepath.to_str().unwrap());
-        let file_name_digest = format!("{:x}", file_digest);
+        let file_name_digest = entry_file_name(absolute_root, filepath);
         let cache_file_path = cache_directory.join(&file_name_digest);
 
-        let file_contents_digest = file_content_digest(filepath)?;
-
         Ok(EmptyCacheEntry {
             filepath: filepath.to_owned(),
-            file_contents_digest,
+            file_contents_digest: String::new(),
+            file_metadata: FileMetadata::for_file(filepath),
             cache_file_path,
             file_name_digest,
         })
     }
+
+    pub fn compute_contents_digest(&mut self) -> anyhow::Result<()> {
+        self.file_contents_digest = file_content_digest(&self.filepath)?;
+        Ok(())
+    }
+}
+
+/// The name of the cache entry for `filepath`. Entries are named by the
+/// file's path relative to `absolute_root`, so a cache built in one checkout
+/// can be used in another.
+pub(crate) fn entry_file_name(absolute_root: &Path, filepath: &Path) -> String {
+    let relative_path =
+        filepath.strip_prefix(absolute_root).unwrap_or(filepath);
+    format!("{:x}", md5::compute(relative_path.to_str().unwrap()))
+}
+
+/// A file's modification time, size and inode when its entry was written. If
+/// they have not changed, neither has the file, so it is not read and hashed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMetadata {
+    pub mtime_secs: u64,
+    pub mtime_nanos: u32,
+    pub size: u64,
+    pub inode: u64,
+}
+
+// A file written again within this long of its last modification may keep
+// the same mtime, so recently modified files are always hashed
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+impl FileMetadata {
+    fn for_file(path: &Path) -> Option<FileMetadata> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?;
+        if SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age < MTIME_GRANULARITY)
+        {
+            return None;
+        }
+        let mtime = modified.duration_since(UNIX_EPOCH).ok()?;
+
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(FileMetadata {
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+            size: metadata.len(),
+            inode,
+        })
+    }
+}
+
+/// Records the fingerprint of the configuration the entries in a cache
+/// directory were built with.
+const FINGERPRINT_FILE_NAME: &str = "pks_fingerprint";
+
+/// Part of the cache fingerprint. Bump it when the name or format of cache
+/// entries changes, so entries written by older builds are discarded.
+pub(crate) const CACHE_FORMAT_VERSION: u32 = 4;
+
+/// Written next to the entries of a `PerFileCache` when it is dropped.
+pub(crate) const LAST_RUN_FILE_NAME: &str = "last_run.json";
+
+/// How many files the last run found in the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRun {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+pub(crate) fn write_last_run(directory: &Path, last_run: LastRun) {
+    if last_run.hits + last_run.misses == 0 {
+        return;
+    }
+    let path = directory.join(LAST_RUN_FILE_NAME);
+    let result = serde_json::to_string(&last_run)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(std::fs::write(&path, json)?));
+    if let Err(e) = result {
+        warn!("Failed to write {:?}: {}", path, e);
+    }
+}
+
+// What pks writes to the cache directory. packwerk may share the directory,
+// so invalidating removes only these.
+const CACHE_ENTRIES: [&str; 4] = [
+    "zeitwerk",
+    "experimental",
+    resolved_references::RESOLVED_REFERENCES_DIRECTORY,
+    "constant_resolver.json",
+];
+
+/// Removes the cached entries in `cache_dir` if they were built with a
+/// different fingerprint (see `Configuration::cache_fingerprint`), or by a
+/// version of pks that did not record one.
+pub(crate) fn invalidate_stale_cache(cache_dir: &Path, fingerprint: &str) {
+    let fingerprint_path = cache_dir.join(FINGERPRINT_FILE_NAME);
+    if std::fs::read_to_string(&fingerprint_path)
+        .is_ok_and(|recorded| recorded == fingerprint)
+    {
+        return;
+    }
+
+    remove_cache_entries(cache_dir);
+    create_cache_dir_idempotently(cache_dir);
+    if let Err(e) = std::fs::write(&fingerprint_path, fingerprint) {
+        warn!("Failed to write {:?}: {}", fingerprint_path, e);
+    }
+}
+
+fn remove_cache_entries(cache_dir: &Path) {
+    for entry in CACHE_ENTRIES {
+        let path = cache_dir.join(entry);
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => debug!("Removed cache entry {:?}", path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                warn!("Failed to remove cache entry {:?}: {}", path, e)
+            }
+        }
+    }
 }
 
 pub fn create_cache_dir_idempotently(cache_dir: &Path) {
     std::fs::create_dir_all(cache_dir)
         .expect("Failed to create cache directory");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidates_entries_built_with_another_fingerprint() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = dir.path();
+        std::fs::create_dir_all(cache_dir.join("zeitwerk")).unwrap();
+        std::fs::write(cache_dir.join("zeitwerk/entry"), "{}").unwrap();
+        std::fs::write(cache_dir.join("constant_resolver.json"), "{}").unwrap();
+        std::fs::write(cache_dir.join("packwerk_entry"), "{}").unwrap();
+
+        invalidate_stale_cache(cache_dir, "first");
+        assert!(!cache_dir.join("zeitwerk").exists());
+        assert!(!cache_dir.join("constant_resolver.json").exists());
+        assert!(cache_dir.join("packwerk_entry").exists());
+
+        std::fs::create_dir_all(cache_dir.join("zeitwerk")).unwrap();
+        invalidate_stale_cache(cache_dir, "first");
+        assert!(cache_dir.join("zeitwerk").exists());
+
+        invalidate_stale_cache(cache_dir, "second");
+        assert!(!cache_dir.join("zeitwerk").exists());
+        assert_eq!(
+            std::fs::read_to_string(cache_dir.join(FINGERPRINT_FILE_NAME))
+                .unwrap(),
+            "second"
+        );
+    }
+}
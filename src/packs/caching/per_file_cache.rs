@@ -6,34 +6,77 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::warn;
 
 use super::cache::Cache;
 use super::CacheResult;
 use super::EmptyCacheEntry;
+use super::FileMetadata;
+use super::{write_last_run, LastRun};
 
 pub struct PerFileCache {
     pub cache_dir: PathBuf,
+    pub absolute_root: PathBuf,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
 }
 
-impl Cache for PerFileCache {
-    fn get(&self, path: &Path) -> anyhow::Result<CacheResult> {
-        let empty_cache_entry = EmptyCacheEntry::new(&self.cache_dir, path)
-            .context(format!("Failed to create cache entry for {:?}", path))?;
-        let cache_entry = CacheEntry::from_empty(&empty_cache_entry)?;
-        if let Some(cache_entry) = cache_entry {
-            let file_digests_match = cache_entry.file_contents_digest
-                == empty_cache_entry.file_contents_digest;
-
-            if !file_digests_match {
-                Ok(CacheResult::Miss(empty_cache_entry))
-            } else {
-                let processed_file = cache_entry.processed_file;
-                Ok(CacheResult::Processed(processed_file))
+impl PerFileCache {
+    pub fn new(cache_dir: PathBuf, absolute_root: PathBuf) -> PerFileCache {
+        PerFileCache {
+            cache_dir,
+            absolute_root,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn lookup(&self, path: &Path) -> anyhow::Result<CacheResult> {
+        let mut empty_cache_entry =
+            EmptyCacheEntry::new(&self.cache_dir, &self.absolute_root, path)
+                .context(format!(
+                    "Failed to create cache entry for {:?}",
+                    path
+                ))?;
+        let Some(cache_entry) = CacheEntry::from_empty(&empty_cache_entry)?
+        else {
+            empty_cache_entry.compute_contents_digest()?;
+            return Ok(CacheResult::Miss(empty_cache_entry));
+        };
+
+        let file_metadata_matches = empty_cache_entry.file_metadata.is_some()
+            && cache_entry.file_metadata == empty_cache_entry.file_metadata;
+        if !file_metadata_matches {
+            empty_cache_entry.compute_contents_digest()?;
+            if cache_entry.file_contents_digest
+                != empty_cache_entry.file_contents_digest
+            {
+                return Ok(CacheResult::Miss(empty_cache_entry));
+            }
+            // The contents are unchanged, e.g. the file was touched or the
+            // cache was imported, so record the metadata for the next run
+            if empty_cache_entry.file_metadata.is_some() {
+                self.write(&empty_cache_entry, &cache_entry.processed_file)?;
             }
-        } else {
-            Ok(CacheResult::Miss(empty_cache_entry))
         }
+
+        let mut processed_file = cache_entry.processed_file;
+        // The entry may have been written in another checkout
+        processed_file.absolute_path = path.to_path_buf();
+        Ok(CacheResult::Processed(processed_file))
+    }
+}
+
+impl Cache for PerFileCache {
+    fn get(&self, path: &Path) -> anyhow::Result<CacheResult> {
+        let result = self.lookup(path)?;
+        let counter = match result {
+            CacheResult::Processed(_) => &self.hits,
+            CacheResult::Miss(_) => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(result)
     }
 
     fn write(
@@ -46,6 +89,7 @@ impl Cache for PerFileCache {
 
         let cache_entry = &CacheEntry {
             file_contents_digest,
+            file_metadata: empty_cache_entry.file_metadata.clone(),
             // Ideally we could pass by reference here, but in practice this cost should be paid on few files
             // that have changed and need to be reprocessed.
             processed_file: processed_file.clone(),
@@ -78,9 +122,25 @@ impl Cache for PerFileCache {
     }
 }
 
+// Recorded for `pks cache stats`
+impl Drop for PerFileCache {
+    fn drop(&mut self) {
+        write_last_run(
+            &self.cache_dir,
+            LastRun {
+                hits: *self.hits.get_mut(),
+                misses: *self.misses.get_mut(),
+            },
+        );
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CacheEntry {
     pub file_contents_digest: String,
+    // Left out when unknown, like in entries written by packwerk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_metadata: Option<FileMetadata>,
     pub processed_file: ProcessedFile,
 }
 
@@ -169,6 +229,7 @@ mod tests {
 
         let expected_serialized = CacheEntry {
             file_contents_digest: "8f9efdcf2caa22fb7b1b4a8274e68d11".to_owned(),
+            file_metadata: None,
             processed_file: ProcessedFile {
                 absolute_path: PathBuf::from("/tests/fixtures/simple_app/packs/foo/app/services/bar/foo.rb"),
                 unresolved_references: vec![UnresolvedReference {
@@ -182,6 +243,9 @@ mod tests {
                     }
                 }],
                 definitions: vec![],
+                parse_errors: vec![],
+                suppressions: vec![],
+                invalid_suppressions: vec![],
             }
         };
 
@@ -195,7 +259,7 @@ mod tests {
 
     #[test]
     fn test_corrupt_cache() -> anyhow::Result<()> {
-        let sha = "e57a05216069923190a4e03d264d9677";
+        let sha = "b36269fac2b09093a3b530301d774e78";
         let corrupt_contents: String = String::from(
             r#"{
   "file_contents_digest":"e57a05216069923190a4e03d264d9677",
@@ -212,6 +276,7 @@ mod tests {
 
         let empty_cache_entry = EmptyCacheEntry::new(
             &cache_path,
+            &PathBuf::from("tests/fixtures/simple_app"),
             &PathBuf::from(
                 "tests/fixtures/simple_app/packs/foo/app/services/foo/bar.rb",
             ),
@@ -222,4 +287,95 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_entries_are_shared_between_checkouts() -> anyhow::Result<()> {
+        let cache_dir = tempfile::TempDir::new()?;
+        let first_checkout = tempfile::TempDir::new()?;
+        let second_checkout = tempfile::TempDir::new()?;
+        for checkout in [&first_checkout, &second_checkout] {
+            fs::create_dir_all(checkout.path().join("app"))?;
+            fs::write(checkout.path().join("app/foo.rb"), "class Foo; end")?;
+        }
+
+        let first_cache = PerFileCache::new(
+            cache_dir.path().to_path_buf(),
+            first_checkout.path().to_path_buf(),
+        );
+        let first_path = first_checkout.path().join("app/foo.rb");
+        let CacheResult::Miss(empty_cache_entry) =
+            first_cache.get(&first_path)?
+        else {
+            panic!("expected a cache miss");
+        };
+        let processed_file = ProcessedFile {
+            absolute_path: first_path,
+            unresolved_references: vec![],
+            definitions: vec![],
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
+        };
+        first_cache.write(&empty_cache_entry, &processed_file)?;
+
+        let second_cache = PerFileCache::new(
+            cache_dir.path().to_path_buf(),
+            second_checkout.path().to_path_buf(),
+        );
+        let second_path = second_checkout.path().join("app/foo.rb");
+        let CacheResult::Processed(cached) = second_cache.get(&second_path)?
+        else {
+            panic!("expected a cache hit");
+        };
+        assert_eq!(cached.absolute_path, second_path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unchanged_metadata_skips_reading_the_file() -> anyhow::Result<()> {
+        let cache_dir = tempfile::TempDir::new()?;
+        let root = tempfile::TempDir::new()?;
+        let path = root.path().join("foo.rb");
+        let an_hour_ago =
+            std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        let write_with_mtime = |contents: &str, mtime| -> anyhow::Result<()> {
+            fs::write(&path, contents)?;
+            File::options()
+                .write(true)
+                .open(&path)?
+                .set_modified(mtime)?;
+            Ok(())
+        };
+        write_with_mtime("class Foo; end", an_hour_ago)?;
+
+        let cache = PerFileCache::new(
+            cache_dir.path().to_path_buf(),
+            root.path().to_path_buf(),
+        );
+        let CacheResult::Miss(empty_cache_entry) = cache.get(&path)? else {
+            panic!("expected a cache miss");
+        };
+        assert!(empty_cache_entry.file_metadata.is_some());
+        let processed_file = ProcessedFile {
+            absolute_path: path.clone(),
+            unresolved_references: vec![],
+            definitions: vec![],
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
+        };
+        cache.write(&empty_cache_entry, &processed_file)?;
+
+        // Same size and mtime: the entry is trusted without hashing
+        write_with_mtime("class Bar; end", an_hour_ago)?;
+        assert!(matches!(cache.get(&path)?, CacheResult::Processed(_)));
+
+        // A different mtime falls back to comparing contents
+        write_with_mtime(
+            "class Bar; end",
+            an_hour_ago + std::time::Duration::from_secs(1),
+        )?;
+        assert!(matches!(cache.get(&path)?, CacheResult::Miss(_)));
+        Ok(())
+    }
 }
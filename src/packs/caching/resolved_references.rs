@@ -0,0 +1,184 @@
+//! The resolved references of each file, persisted so a warm run can skip
+//! resolving the references of unchanged files as well as parsing them.
+//!
+//! A file's resolved references depend on its unresolved references and on
+//! what every other file defines, so an entry is only used if both the digest
+//! of the file's unresolved references and the fingerprint of the constant
+//! definitions and packs match the current run.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{entry_file_name, write_last_run, LastRun};
+use crate::packs::checker::reference::Reference;
+use crate::packs::constant_resolver::ConstantResolver;
+use crate::packs::{Configuration, ProcessedFile};
+
+/// Where the entries live, inside the cache directory.
+pub(crate) const RESOLVED_REFERENCES_DIRECTORY: &str = "resolved_references";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolvedReferencesEntry {
+    pub unresolved_references_digest: String,
+    pub definitions_fingerprint: String,
+    pub references: Vec<Reference>,
+}
+
+pub struct ResolvedReferencesCache {
+    cache_dir: PathBuf,
+    absolute_root: PathBuf,
+    definitions_fingerprint: String,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl ResolvedReferencesCache {
+    pub fn new(
+        configuration: &Configuration,
+        constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    ) -> ResolvedReferencesCache {
+        ResolvedReferencesCache {
+            cache_dir: configuration
+                .cache_directory
+                .join(RESOLVED_REFERENCES_DIRECTORY),
+            absolute_root: configuration.absolute_root.clone(),
+            definitions_fingerprint: definitions_fingerprint(
+                configuration,
+                constant_resolver,
+            ),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// The cached references of `processed_file`, if they are still valid.
+    pub fn get(
+        &self,
+        processed_file: &ProcessedFile,
+    ) -> anyhow::Result<Option<Vec<Reference>>> {
+        let path = self.entry_path(processed_file);
+        let entry = match read_entry(&path) {
+            Ok(entry) => Some(entry),
+            Err(_) if !path.exists() => None,
+            Err(e) => {
+                warn!("Failed to read cache file {:?}: {:#}", path, e);
+                None
+            }
+        };
+        let references = entry
+            .filter(|entry| {
+                entry.definitions_fingerprint == self.definitions_fingerprint
+                    && entry.unresolved_references_digest
+                        == unresolved_references_digest(processed_file)
+            })
+            .map(|entry| entry.references);
+
+        let counter = if references.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(references)
+    }
+
+    pub fn write(
+        &self,
+        processed_file: &ProcessedFile,
+        references: Vec<Reference>,
+    ) -> anyhow::Result<Vec<Reference>> {
+        let entry = ResolvedReferencesEntry {
+            unresolved_references_digest: unresolved_references_digest(
+                processed_file,
+            ),
+            definitions_fingerprint: self.definitions_fingerprint.clone(),
+            references,
+        };
+        let path = self.entry_path(processed_file);
+        std::fs::create_dir_all(&self.cache_dir).context(format!(
+            "Failed to create cache directory {:?}",
+            self.cache_dir
+        ))?;
+        std::fs::write(&path, serde_json::to_string(&entry)?)
+            .context(format!("Failed to create cache file {:?}", path))?;
+        Ok(entry.references)
+    }
+
+    fn entry_path(&self, processed_file: &ProcessedFile) -> PathBuf {
+        self.cache_dir.join(entry_file_name(
+            &self.absolute_root,
+            &processed_file.absolute_path,
+        ))
+    }
+}
+
+// Recorded for `pks cache stats`
+impl Drop for ResolvedReferencesCache {
+    fn drop(&mut self) {
+        write_last_run(
+            &self.cache_dir,
+            LastRun {
+                hits: *self.hits.get_mut(),
+                misses: *self.misses.get_mut(),
+            },
+        );
+    }
+}
+
+pub(crate) fn read_entry(
+    path: &Path,
+) -> anyhow::Result<ResolvedReferencesEntry> {
+    let file =
+        File::open(path).context(format!("Failed to open file {:?}", path))?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .context("Failed to deserialize ResolvedReferencesEntry")
+}
+
+// Covers the file's `pks:disable` comments too, since they are copied onto
+// its references
+fn unresolved_references_digest(processed_file: &ProcessedFile) -> String {
+    let serialized = serde_json::to_vec(&(
+        &processed_file.unresolved_references,
+        &processed_file.suppressions,
+    ))
+    .unwrap_or_default();
+    format!("{:x}", md5::compute(serialized))
+}
+
+// Changes whenever a constant is defined in another file, or a file moves to
+// another pack, since either can change how a reference resolves
+fn definitions_fingerprint(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+) -> String {
+    let root = &configuration.absolute_root;
+    let mut lines: Vec<String> = constant_resolver
+        .fully_qualified_constant_name_to_constant_definition_map()
+        .iter()
+        .map(|(name, definitions)| {
+            let mut paths: Vec<&Path> = definitions
+                .iter()
+                .map(|definition| {
+                    let path = &definition.absolute_path_of_definition;
+                    path.strip_prefix(root).unwrap_or(path)
+                })
+                .collect();
+            paths.sort();
+            format!("constant {} {:?}", name, paths)
+        })
+        .collect();
+    lines.extend(configuration.pack_set.packs.iter().map(|pack| {
+        format!(
+            "pack {} {:?}",
+            pack.name,
+            pack.yml.strip_prefix(root).unwrap_or(&pack.yml)
+        )
+    }));
+    lines.sort();
+    format!("{:x}", md5::compute(lines.join("\n")))
+}
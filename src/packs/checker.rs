@@ -18,6 +18,7 @@ use crate::packs::Configuration;
 use crate::packs::SourceLocation;
 
 use anyhow::bail;
+use itertools::Itertools;
 // External imports
 use rayon::prelude::IntoParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
@@ -28,8 +29,15 @@ use std::{collections::HashSet, path::PathBuf};
 use tracing::debug;
 
 use super::bin_locater;
-use super::reference_extractor::get_all_references;
-
+use super::git;
+use super::parsing::process_file_contents;
+use super::reference_extractor::{
+    extract_references, get_all_references, get_constant_resolver,
+    resolve_references, ExtractedReferences,
+};
+use super::{ParseDiagnostic, ProcessedFile};
+
+/// What makes a violation the same violation as one in package_todo.yml.
 #[derive(PartialEq, Clone, Eq, Hash, Debug)]
 pub struct ViolationIdentifier {
     pub violation_type: CheckerType,
@@ -74,11 +82,40 @@ pub(crate) trait ValidatorInterface {
     fn validate(&self, configuration: &Configuration) -> Option<Vec<String>>;
 }
 
+/// A syntax error in a checked file. The file's violations may be missing
+/// from the result, since references past the error could not be extracted.
+/// Also used for `# pks:` comments that could not be read.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// A violation suppressed by a `# pks:disable` comment. It is neither
+/// reported nor recorded in package_todo.yml.
+#[derive(PartialEq, Clone, Eq, Hash, Debug)]
+pub struct SuppressedViolation {
+    pub violation: Violation,
+    pub reason: String,
+}
+
+/// The outcome of checking an application, after comparing the violations
+/// found against those recorded in package_todo.yml files.
 #[derive(Debug, PartialEq)]
 pub struct CheckAllResult {
     pub reportable_violations: HashSet<Violation>,
     pub stale_violations: Vec<ViolationIdentifier>,
     pub strict_mode_violations: HashSet<Violation>,
+    /// Not violations themselves; they only fail a check with
+    /// `fail_on_parse_errors`
+    pub parse_errors: Vec<ParseError>,
+    /// Ordered by location
+    pub suppressed_violations: Vec<SuppressedViolation>,
+    /// `# pks:` comments that could not be read, ordered by location. They
+    /// fail a check, since a suppression is meant to be reviewed.
+    pub invalid_suppressions: Vec<ParseError>,
 }
 
 impl CheckAllResult {
@@ -86,6 +123,31 @@ impl CheckAllResult {
         !self.reportable_violations.is_empty()
             || !self.stale_violations.is_empty()
             || !self.strict_mode_violations.is_empty()
+            || !self.invalid_suppressions.is_empty()
+    }
+
+    /// Whether the parse errors alone should fail the check.
+    pub fn fails_on_parse_errors(&self, configuration: &Configuration) -> bool {
+        configuration.fail_on_parse_errors && !self.parse_errors.is_empty()
+    }
+
+    /// Reportable and strict violations, each once, ordered by location.
+    /// A strict violation is also reportable unless it was recorded, so it
+    /// can show up in both sets.
+    pub(crate) fn sorted_violations(&self) -> Vec<&Violation> {
+        self.reportable_violations
+            .iter()
+            .chain(&self.strict_mode_violations)
+            .unique()
+            .sorted_by_key(|v| {
+                (
+                    &v.identifier.file,
+                    v.source_location.line,
+                    v.source_location.column,
+                    v.identifier.violation_type.to_string(),
+                )
+            })
+            .collect()
     }
 }
 struct CheckAllBuilder<'a> {
@@ -98,6 +160,15 @@ struct FoundViolations {
     violations: HashSet<Violation>,
 }
 
+/// What the checkers found among a set of references.
+#[derive(Debug, Default)]
+struct CheckedReferences {
+    violations: HashSet<Violation>,
+    suppressed_violations: HashSet<SuppressedViolation>,
+    /// In the files the references are from
+    invalid_suppressions: Vec<ParseError>,
+}
+
 impl<'a> CheckAllBuilder<'a> {
     fn new(
         configuration: &'a Configuration,
@@ -109,7 +180,12 @@ impl<'a> CheckAllBuilder<'a> {
         }
     }
 
-    pub fn build(mut self) -> anyhow::Result<CheckAllResult> {
+    pub fn build(
+        mut self,
+        parse_errors: Vec<ParseError>,
+        suppressed_violations: HashSet<SuppressedViolation>,
+        invalid_suppressions: Vec<ParseError>,
+    ) -> anyhow::Result<CheckAllResult> {
         let recorded_violations = &self.configuration.pack_set.all_violations;
 
         Ok(CheckAllResult {
@@ -127,6 +203,26 @@ impl<'a> CheckAllBuilder<'a> {
                 .build_strict_mode_violations()
                 .into_iter()
                 .collect(),
+            parse_errors,
+            suppressed_violations: suppressed_violations
+                .into_iter()
+                .sorted_by(|a, b| {
+                    let (a, b) = (&a.violation, &b.violation);
+                    (
+                        &a.identifier.file,
+                        a.source_location.line,
+                        a.source_location.column,
+                        a.identifier.violation_type.to_string(),
+                    )
+                        .cmp(&(
+                            &b.identifier.file,
+                            b.source_location.line,
+                            b.source_location.column,
+                            b.identifier.violation_type.to_string(),
+                        ))
+                })
+                .collect(),
+            invalid_suppressions,
         })
     }
 
@@ -230,19 +326,127 @@ pub(crate) fn check_all(
     let absolute_paths: HashSet<PathBuf> =
         configuration.intersect_files(files.clone());
 
-    let violations: HashSet<Violation> =
+    let (checked, parse_errors) =
+        get_all_violations(configuration, &absolute_paths, &checkers)?;
+    build_check_all_result(configuration, absolute_paths, checked, parse_errors)
+}
+
+/// Like `check_all`, but for references the caller has already resolved.
+/// The language server uses this to check a single buffer against a constant
+/// resolver it keeps in memory, rather than rebuilding it for every change.
+pub(crate) fn check_references(
+    configuration: &Configuration,
+    absolute_paths: HashSet<PathBuf>,
+    references: &[Reference],
+    parse_errors: Vec<ParseError>,
+    invalid_suppressions: Vec<ParseError>,
+) -> anyhow::Result<CheckAllResult> {
+    let checkers = get_checkers(configuration);
+    let mut checked = run_checkers(configuration, references, &checkers)?;
+    checked.invalid_suppressions = invalid_suppressions;
+    build_check_all_result(configuration, absolute_paths, checked, parse_errors)
+}
+
+/// Like `check_all`, but only checks the files changed since the git ref
+/// `base`, and only reports the violations that did not exist there. Unlike
+/// package_todo.yml files, this needs nothing to be recorded up front.
+pub(crate) fn check_since(
+    configuration: &Configuration,
+    base: &str,
+    files: Vec<String>,
+) -> anyhow::Result<CheckAllResult> {
+    let root = &configuration.absolute_root;
+    git::verify_ref(root, base)?;
+    let changed_files = git::changed_files(root, base)?;
+    let absolute_paths: HashSet<PathBuf> = configuration
+        .intersect_files(files)
+        .into_iter()
+        .filter(|path| changed_files.contains(path))
+        .collect();
+    debug!(
+        "Checking {} files changed since {}",
+        absolute_paths.len(),
+        base
+    );
+
+    let checkers = get_checkers(configuration);
+    let (mut checked, parse_errors) =
         get_all_violations(configuration, &absolute_paths, &checkers)?;
+    let base_violations =
+        get_base_violations(configuration, base, &absolute_paths, &checkers)?;
+    checked
+        .violations
+        .retain(|v| !base_violations.contains(&v.identifier));
+
+    let mut result = build_check_all_result(
+        configuration,
+        absolute_paths,
+        checked,
+        parse_errors,
+    )?;
+    // Recorded violations that went away are not new violations
+    result.stale_violations.clear();
+    Ok(result)
+}
+
+// The violations in the files as they were at `base`. Constants are resolved
+// against the current tree, so a reference only counts as existing if it
+// pointed at the same pack before.
+fn get_base_violations(
+    configuration: &Configuration,
+    base: &str,
+    absolute_paths: &HashSet<PathBuf>,
+    checkers: &Vec<Box<dyn CheckerInterface + Send + Sync>>,
+) -> anyhow::Result<HashSet<ViolationIdentifier>> {
+    debug!("Processing files as of {}", base);
+    let root = &configuration.absolute_root;
+    let processed_files: Vec<ProcessedFile> = absolute_paths
+        .par_iter()
+        .map(|absolute_path| {
+            let relative_path = absolute_path.strip_prefix(root)?;
+            Ok(git::file_at(root, base, relative_path)?.map(|contents| {
+                process_file_contents(absolute_path, contents, configuration)
+            }))
+        })
+        .collect::<anyhow::Result<Vec<Option<ProcessedFile>>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let constant_resolver = get_constant_resolver(configuration)?;
+    let references = resolve_references(
+        configuration,
+        constant_resolver.as_ref(),
+        &processed_files,
+    )?;
+    Ok(run_checkers(configuration, &references, checkers)?
+        .violations
+        .into_iter()
+        .map(|v| v.identifier)
+        .collect())
+}
+
+fn build_check_all_result(
+    configuration: &Configuration,
+    absolute_paths: HashSet<PathBuf>,
+    checked: CheckedReferences,
+    parse_errors: Vec<ParseError>,
+) -> anyhow::Result<CheckAllResult> {
     let found_violations = FoundViolations {
         absolute_paths,
-        violations,
+        violations: checked.violations,
     };
     debug!("Building check-all result (diffing against package_todo.yml)");
-    let result = CheckAllBuilder::new(configuration, &found_violations).build();
+    let result = CheckAllBuilder::new(configuration, &found_violations).build(
+        parse_errors,
+        checked.suppressed_violations,
+        checked.invalid_suppressions,
+    );
     debug!("Finished building check-all result");
     result
 }
 
-fn validate(configuration: &Configuration) -> Vec<String> {
+pub(crate) fn validate(configuration: &Configuration) -> Vec<String> {
     debug!("Running validators against packages");
     let validators: Vec<Box<dyn ValidatorInterface + Send + Sync>> = vec![
         Box::new(dependency::Checker {
@@ -303,7 +507,9 @@ pub(crate) fn update(configuration: &Configuration) -> anyhow::Result<()> {
         configuration,
         &configuration.included_files,
         &checkers,
-    )?;
+    )?
+    .0
+    .violations;
 
     let strict_violations = &violations
         .iter()
@@ -326,6 +532,32 @@ pub(crate) fn update(configuration: &Configuration) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Every violation in the included files, recorded or not.
+pub(crate) fn find_all_violations(
+    configuration: &Configuration,
+) -> anyhow::Result<HashSet<Violation>> {
+    let checkers = get_checkers(configuration);
+    let violations = get_all_violations(
+        configuration,
+        &configuration.included_files,
+        &checkers,
+    )?
+    .0
+    .violations;
+    Ok(violations)
+}
+
+/// The violations among `references`, recorded or not.
+pub(crate) fn violations_for_references(
+    configuration: &Configuration,
+    references: &[Reference],
+) -> anyhow::Result<HashSet<Violation>> {
+    Ok(
+        run_checkers(configuration, references, &get_checkers(configuration))?
+            .violations,
+    )
+}
+
 pub(crate) fn remove_unnecessary_dependencies(
     configuration: &Configuration,
 ) -> anyhow::Result<()> {
@@ -367,12 +599,14 @@ pub(crate) fn check_unnecessary_dependencies(
     }
 }
 
-fn get_unnecessary_dependencies(
+/// Counts the references from each pack to each other pack, keyed by
+/// (referencing pack name, defining pack name).
+pub(crate) fn get_pack_reference_counts(
     configuration: &Configuration,
-) -> anyhow::Result<HashMap<Pack, Vec<String>>> {
+) -> anyhow::Result<HashMap<(String, String), usize>> {
     let references =
         get_all_references(configuration, &configuration.included_files)?;
-    let mut edge_counts: HashMap<(String, String), i32> = HashMap::new();
+    let mut edge_counts: HashMap<(String, String), usize> = HashMap::new();
     for reference in references {
         let defining_pack_name = reference.defining_pack_name;
         if let Some(defining_pack_name) = defining_pack_name {
@@ -385,6 +619,13 @@ fn get_unnecessary_dependencies(
                 .or_insert(1);
         }
     }
+    Ok(edge_counts)
+}
+
+fn get_unnecessary_dependencies(
+    configuration: &Configuration,
+) -> anyhow::Result<HashMap<Pack, Vec<String>>> {
+    let edge_counts = get_pack_reference_counts(configuration)?;
 
     let mut unnecessary_dependencies: HashMap<Pack, Vec<String>> =
         HashMap::new();
@@ -408,33 +649,119 @@ fn get_all_violations(
     configuration: &Configuration,
     absolute_paths: &HashSet<PathBuf>,
     checkers: &Vec<Box<dyn CheckerInterface + Send + Sync>>,
-) -> anyhow::Result<HashSet<Violation>> {
-    let references = get_all_references(configuration, absolute_paths)?;
+) -> anyhow::Result<(CheckedReferences, Vec<ParseError>)> {
+    let ExtractedReferences {
+        references,
+        parse_errors,
+        invalid_suppressions,
+        ..
+    } = extract_references(configuration, absolute_paths)?;
+    let mut checked = run_checkers(configuration, &references, checkers)?;
+    checked.invalid_suppressions = invalid_suppressions;
+
+    // Dropping the reference vector deallocates several million Strings on a
+    // large codebase. It is measured explicitly so it shows up as its own phase
+    // in `--debug` output rather than hiding in the gap before process exit.
+    drop(references);
+    debug!("Dropped resolved references");
+
+    Ok((checked, parse_errors))
+}
+
+/// The syntax errors in `processed_files`, ordered by location.
+pub(crate) fn parse_errors_for<'a>(
+    configuration: &Configuration,
+    processed_files: impl IntoIterator<Item = &'a ProcessedFile>,
+) -> Vec<ParseError> {
+    diagnostics_for(configuration, processed_files, |processed_file| {
+        &processed_file.parse_errors
+    })
+}
+
+/// The `# pks:` comments in `processed_files` that could not be read,
+/// ordered by location.
+pub(crate) fn invalid_suppressions_for<'a>(
+    configuration: &Configuration,
+    processed_files: impl IntoIterator<Item = &'a ProcessedFile>,
+) -> Vec<ParseError> {
+    diagnostics_for(configuration, processed_files, |processed_file| {
+        &processed_file.invalid_suppressions
+    })
+}
+
+fn diagnostics_for<'a>(
+    configuration: &Configuration,
+    processed_files: impl IntoIterator<Item = &'a ProcessedFile>,
+    diagnostics: impl Fn(&'a ProcessedFile) -> &'a Vec<ParseDiagnostic>,
+) -> Vec<ParseError> {
+    let mut parse_errors: Vec<ParseError> = processed_files
+        .into_iter()
+        .flat_map(|processed_file| {
+            let file = processed_file
+                .absolute_path
+                .strip_prefix(&configuration.absolute_root)
+                .unwrap_or(&processed_file.absolute_path)
+                .display()
+                .to_string();
+            diagnostics(processed_file).iter().map(move |e| ParseError {
+                file: file.clone(),
+                line: e.line,
+                column: e.column,
+                message: e.message.clone(),
+            })
+        })
+        .collect();
+    parse_errors.sort_by(|a, b| {
+        (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column))
+    });
+    parse_errors
+}
+
+// Violations of references with a matching `# pks:disable` comment are set
+// aside as suppressed. Strict violations cannot be suppressed, the same way
+// they cannot be recorded.
+fn run_checkers(
+    configuration: &Configuration,
+    references: &[Reference],
+    checkers: &Vec<Box<dyn CheckerInterface + Send + Sync>>,
+) -> anyhow::Result<CheckedReferences> {
     debug!("Running checkers on resolved references");
 
     let violations = checkers
         .into_par_iter()
-        .try_fold(HashSet::new, |mut acc, c| {
-            for reference in &references {
-                if let Some(violation) = c.check(reference, configuration)? {
-                    acc.insert(violation);
+        .try_fold(CheckedReferences::default, |mut acc, c| {
+            for reference in references {
+                let Some(violation) = c.check(reference, configuration)? else {
+                    continue;
+                };
+                let suppression = if violation.identifier.strict {
+                    None
+                } else {
+                    reference
+                        .suppression_for(&violation.identifier.violation_type)
+                };
+                match suppression {
+                    Some(suppression) => {
+                        acc.suppressed_violations.insert(SuppressedViolation {
+                            violation,
+                            reason: suppression.reason.clone(),
+                        });
+                    }
+                    None => {
+                        acc.violations.insert(violation);
+                    }
                 }
             }
             Ok(acc)
         })
-        .try_reduce(HashSet::new, |mut acc, v| {
-            acc.extend(v);
+        .try_reduce(CheckedReferences::default, |mut acc, v| {
+            acc.violations.extend(v.violations);
+            acc.suppressed_violations.extend(v.suppressed_violations);
             Ok(acc)
         });
 
     debug!("Finished running checkers");
 
-    // Dropping the reference vector deallocates several million Strings on a
-    // large codebase. It is measured explicitly so it shows up as its own phase
-    // in `--debug` output rather than hiding in the gap before process exit.
-    drop(references);
-    debug!("Dropped resolved references");
-
     violations
 }
 
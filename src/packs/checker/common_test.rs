@@ -140,6 +140,7 @@ pub mod tests {
                 "packs/bar/app/services/public/bar.rb",
             )),
             source_location: SourceLocation { line: 3, column: 1 },
+            suppressions: vec![],
         });
 
         let root_pack = Pack {
@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::pack_checker::PackChecker;
-use super::{CheckerInterface, ValidatorInterface};
+use super::{get_pack_reference_counts, CheckerInterface, ValidatorInterface};
+use crate::packs::bin_locater;
 use crate::packs::checker::Reference;
 use crate::packs::checker_configuration::CheckerConfiguration;
 use crate::packs::pack::Pack;
 use crate::packs::{Configuration, Violation};
 use anyhow::Context;
 use petgraph::algo::tarjan_scc;
-use petgraph::prelude::DiGraph;
+use petgraph::prelude::{DiGraph, NodeIndex};
+
+const MAX_CYCLES_PER_COMPONENT: usize = 20;
 
 pub struct Checker {
     pub checker_configuration: CheckerConfiguration,
@@ -65,25 +68,53 @@ impl ValidatorInterface for Checker {
             }
         }
 
-        let mut sccs = vec![];
-        let strongly_componented_components = tarjan_scc(&graph);
-        for component in strongly_componented_components {
-            if component.len() > 1 {
-                let pack_names: Vec<String> = component
-                    .iter()
-                    .map(|node_index| {
-                        let pack = node_to_pack
-                            .get(node_index)
-                            .expect("Could not find pack name for node index");
-                        pack.name.to_owned()
-                    })
-                    .collect();
-                sccs.push(pack_names.join(", "));
-            }
-        }
-
-        if !sccs.is_empty() {
-            let sccs_display = sccs.join("\n\n");
+        let components: Vec<Vec<NodeIndex>> = tarjan_scc(&graph)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .collect();
+
+        if !components.is_empty() {
+            let pack_name = |node_index: &NodeIndex| -> &str {
+                &node_to_pack
+                    .get(node_index)
+                    .expect("Could not find pack name for node index")
+                    .name
+            };
+            // Counting references means parsing the whole codebase, while
+            // validating otherwise only reads package.yml files
+            let reference_counts = if configuration.explain_cycles {
+                match get_pack_reference_counts(configuration) {
+                    Ok(reference_counts) => Some(reference_counts),
+                    Err(err) => {
+                        error_messages.push(format!(
+                            "Could not count the references between packs in dependency cycles: {:#}",
+                            err
+                        ));
+                        Some(HashMap::new())
+                    }
+                }
+            } else {
+                None
+            };
+
+            let sccs_display = components
+                .iter()
+                .map(|component| {
+                    let pack_names: Vec<&str> =
+                        component.iter().map(pack_name).collect();
+                    let cycles: Vec<Vec<&str>> =
+                        shortest_cycles(&graph, component)
+                            .iter()
+                            .map(|cycle| cycle.iter().map(pack_name).collect())
+                            .collect();
+                    format!(
+                        "{}\n{}",
+                        pack_names.join(", "),
+                        describe_cycles(&cycles, reference_counts.as_ref())
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n\n");
 
             let error_message = format!(
                 "
@@ -91,7 +122,7 @@ Found {} strongly connected components (i.e. dependency cycles)
 The following groups of packages form a cycle:
 
 {}",
-                sccs.len(),
+                components.len(),
                 sccs_display
             );
             error_messages.push(error_message);
@@ -105,6 +136,157 @@ The following groups of packages form a cycle:
     }
 }
 
+// For each dependency within the component, the shortest cycle through it,
+// without duplicates. Together these cover every dependency in the component,
+// and unlike enumerating every elementary cycle, finding them stays cheap for
+// components with dozens of packs.
+fn shortest_cycles(
+    graph: &DiGraph<(), ()>,
+    component: &[NodeIndex],
+) -> Vec<Vec<NodeIndex>> {
+    let members: HashSet<NodeIndex> = component.iter().copied().collect();
+    let mut cycles: Vec<Vec<NodeIndex>> = vec![];
+    for &from in component {
+        for to in graph.neighbors(from) {
+            if !members.contains(&to) {
+                continue;
+            }
+            if let Some(path) = shortest_path(graph, &members, to, from) {
+                // path runs from `to` back to `from`
+                let mut cycle = vec![from];
+                cycle.extend(&path[..path.len() - 1]);
+                if !cycles.iter().any(|c| is_rotation(c, &cycle)) {
+                    cycles.push(cycle);
+                }
+            }
+        }
+    }
+    cycles
+}
+
+fn shortest_path(
+    graph: &DiGraph<(), ()>,
+    members: &HashSet<NodeIndex>,
+    start: NodeIndex,
+    goal: NodeIndex,
+) -> Option<Vec<NodeIndex>> {
+    let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        if node == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&prev) = previous.get(&current) {
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in graph.neighbors(node) {
+            if members.contains(&next)
+                && next != start
+                && !previous.contains_key(&next)
+            {
+                previous.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+fn is_rotation(a: &[NodeIndex], b: &[NodeIndex]) -> bool {
+    a.len() == b.len()
+        && (0..a.len()).any(|offset| {
+            a.iter().cycle().skip(offset).take(a.len()).eq(b.iter())
+        })
+}
+
+// Lists the cycles as paths, then (given reference counts) every dependency
+// in them with the number of references backing it, so the cheapest
+// dependencies to remove are first.
+fn describe_cycles(
+    cycles: &[Vec<&str>],
+    reference_counts: Option<&HashMap<(String, String), usize>>,
+) -> String {
+    let mut paths: Vec<Vec<&str>> = cycles
+        .iter()
+        .map(|cycle| {
+            let first = (0..cycle.len())
+                .min_by_key(|&i| cycle[i])
+                .expect("cycles are not empty");
+            let mut path: Vec<&str> = cycle
+                .iter()
+                .cycle()
+                .skip(first)
+                .take(cycle.len())
+                .copied()
+                .collect();
+            path.push(path[0]);
+            path
+        })
+        .collect();
+    paths.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    let mut lines = vec!["Cycles:".to_string()];
+    for path in paths.iter().take(MAX_CYCLES_PER_COMPONENT) {
+        lines.push(format!("  {}", path.join(" → ")));
+    }
+    if paths.len() > MAX_CYCLES_PER_COMPONENT {
+        lines.push(format!(
+            "  ... and {} more",
+            paths.len() - MAX_CYCLES_PER_COMPONENT
+        ));
+    }
+    let Some(reference_counts) = reference_counts else {
+        lines.push(format!(
+            "Run `{} validate --explain-cycles` to see how many references back each dependency.",
+            bin_locater::packs_bin_name()
+        ));
+        return lines.join("\n");
+    };
+
+    let mut cycles_per_edge: HashMap<(&str, &str), usize> = HashMap::new();
+    for path in &paths {
+        for edge in path.windows(2) {
+            *cycles_per_edge.entry((edge[0], edge[1])).or_default() += 1;
+        }
+    }
+    let mut edges: Vec<((&str, &str), usize, usize)> = cycles_per_edge
+        .into_iter()
+        .map(|((from, to), cycle_count)| {
+            let references = reference_counts
+                .get(&(from.to_string(), to.to_string()))
+                .copied()
+                .unwrap_or(0);
+            ((from, to), references, cycle_count)
+        })
+        .collect();
+    edges.sort_by(|(a_edge, a_refs, a_cycles), (b_edge, b_refs, b_cycles)| {
+        a_refs
+            .cmp(b_refs)
+            .then_with(|| b_cycles.cmp(a_cycles))
+            .then_with(|| a_edge.cmp(b_edge))
+    });
+
+    lines.push(
+        "Dependencies in these cycles, fewest references first:".to_string(),
+    );
+    for ((from, to), references, cycle_count) in edges {
+        lines.push(format!(
+            "  {} → {}: {} reference{}, in {} cycle{}",
+            from,
+            to,
+            references,
+            if references == 1 { "" } else { "s" },
+            cycle_count,
+            if cycle_count == 1 { "" } else { "s" },
+        ));
+    }
+    lines.join("\n")
+}
+
 // TODO: Add test for does not enforce dependencies
 impl CheckerInterface for Checker {
     fn check(
@@ -346,13 +528,14 @@ mod tests {
                 CheckerType::Dependency,
             ),
         };
-        let configuration = configuration::get(
+        let mut configuration = configuration::get(
             PathBuf::from("tests/fixtures/app_with_dependency_cycles")
                 .canonicalize()
                 .expect("Could not canonicalize path")
                 .as_path(),
         )
         .unwrap();
+        configuration.explain_cycles = true;
 
         let error = checker.validate(&configuration);
         let expected_message = vec![String::from("Package cannot list itself as a dependency: packs/baz/package.yml"),
@@ -361,7 +544,12 @@ mod tests {
 Found 1 strongly connected components (i.e. dependency cycles)
 The following groups of packages form a cycle:
 
-packs/foo, packs/bar",
+packs/foo, packs/bar
+Cycles:
+  packs/bar → packs/foo → packs/bar
+Dependencies in these cycles, fewest references first:
+  packs/bar → packs/foo: 0 references, in 1 cycle
+  packs/foo → packs/bar: 0 references, in 1 cycle",
         )];
         assert_eq!(error, Some(expected_message));
     }
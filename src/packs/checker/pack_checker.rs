@@ -259,6 +259,7 @@ mod tests {
                 line: 3usize,
                 column: 4usize,
             },
+            suppressions: vec![],
         };
         (config, refer)
     }
@@ -295,6 +295,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -330,6 +331,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -366,6 +368,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -403,6 +406,7 @@ mod tests {
                     "packs/bar/app/services/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -443,6 +447,7 @@ mod tests {
                     "packs/bar/app/services/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -486,6 +491,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -526,6 +532,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -566,6 +573,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -605,6 +613,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             }),
             configuration: None,
             defining_pack: None,
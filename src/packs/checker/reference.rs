@@ -1,13 +1,17 @@
 use std::path::Path;
 
 use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
 
 use crate::packs::{
     constant_resolver::ConstantResolver, pack::Pack,
-    parsing::UnresolvedReference, Configuration, PackSet, SourceLocation,
+    parsing::UnresolvedReference, CheckerType, Configuration, PackSet,
+    SourceLocation, Suppression,
 };
 
-#[derive(Debug)]
+/// A constant referenced from a file, resolved to the pack that defines it
+/// (if any).
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Reference {
     pub constant_name: String,
     pub defining_pack_name: Option<String>,
@@ -15,6 +19,9 @@ pub struct Reference {
     pub referencing_pack_name: String,
     pub relative_referencing_file: String,
     pub source_location: SourceLocation,
+    /// The `# pks:disable` comments covering the reference's line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
 }
 
 impl Reference {
@@ -42,6 +49,17 @@ impl Reference {
         context(format!("Reference#referencing_pack_name is {}, but that pack is not found in pack set.", 
         self.referencing_pack_name))
     }
+
+    /// The comment suppressing `violation_type` violations of this reference,
+    /// if any.
+    pub fn suppression_for(
+        &self,
+        violation_type: &CheckerType,
+    ) -> Option<&Suppression> {
+        self.suppressions
+            .iter()
+            .find(|s| s.violation_types.contains(violation_type))
+    }
 }
 
 impl Reference {
@@ -116,6 +134,7 @@ impl Reference {
                             .clone(),
                         source_location: source_location.clone(),
                         relative_defining_file,
+                        suppressions: vec![],
                     })
                 })
                 .collect::<anyhow::Result<Vec<Reference>>>()?)
@@ -132,6 +151,7 @@ impl Reference {
                 relative_referencing_file,
                 source_location,
                 relative_defining_file,
+                suppressions: vec![],
             }])
         }
     }
@@ -0,0 +1,138 @@
+//! Checkstyle XML output formatter for `pks check -o checkstyle`.
+//!
+//! Writes one `<file>` element per file with violations, and one `<error>`
+//! per violation carrying its line and column.
+//! Stale TODOs have no location in the code, so they are not reported.
+//! Parse errors are reported as warnings, or errors with
+//! `fail_on_parse_errors`. Invalid `# pks:` comments are always errors.
+
+use std::collections::BTreeMap;
+
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::Writer;
+
+use super::checker::{CheckAllResult, ParseError, Violation};
+use super::json::build_message;
+use super::Configuration;
+
+// Errors for one `<file>` element: violations, then parse errors, then
+// invalid `# pks:` comments.
+#[derive(Default)]
+struct FileErrors<'a> {
+    violations: Vec<&'a Violation>,
+    parse_errors: Vec<&'a ParseError>,
+    invalid_suppressions: Vec<&'a ParseError>,
+}
+
+fn write_located_error<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    error: &ParseError,
+    severity: &str,
+    source: &str,
+) -> std::io::Result<()> {
+    let line = error.line.to_string();
+    let column = (error.column + 1).to_string();
+    writer
+        .create_element("error")
+        .with_attribute(("line", line.as_str()))
+        .with_attribute(("column", column.as_str()))
+        .with_attribute(("severity", severity))
+        .with_attribute(("message", error.message.as_str()))
+        .with_attribute(("source", source))
+        .write_empty()?;
+    Ok(())
+}
+
+pub fn write_checkstyle<W: std::io::Write>(
+    result: &CheckAllResult,
+    config: &Configuration,
+    writer: W,
+) -> anyhow::Result<()> {
+    let mut errors_by_file: BTreeMap<&str, FileErrors> = BTreeMap::new();
+    for v in result.sorted_violations() {
+        errors_by_file
+            .entry(&v.identifier.file)
+            .or_default()
+            .violations
+            .push(v);
+    }
+    for parse_error in &result.parse_errors {
+        errors_by_file
+            .entry(&parse_error.file)
+            .or_default()
+            .parse_errors
+            .push(parse_error);
+    }
+    for invalid_suppression in &result.invalid_suppressions {
+        errors_by_file
+            .entry(&invalid_suppression.file)
+            .or_default()
+            .invalid_suppressions
+            .push(invalid_suppression);
+    }
+
+    let mut writer = Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new(
+        "1.0",
+        Some("UTF-8"),
+        None,
+    )))?;
+    writer
+        .create_element("checkstyle")
+        .with_attribute(("version", "4.3"))
+        .write_inner_content(|writer| {
+            for (file, errors) in &errors_by_file {
+                writer
+                    .create_element("file")
+                    .with_attribute(("name", *file))
+                    .write_inner_content(|writer| {
+                        for v in &errors.violations {
+                            let line = v.source_location.line.to_string();
+                            // Checkstyle columns are 1-based, ours are 0-based
+                            let column =
+                                (v.source_location.column + 1).to_string();
+                            let source =
+                                format!("pks.{}", v.identifier.violation_type);
+                            writer
+                                .create_element("error")
+                                .with_attribute(("line", line.as_str()))
+                                .with_attribute(("column", column.as_str()))
+                                .with_attribute(("severity", "error"))
+                                .with_attribute((
+                                    "message",
+                                    build_message(v, config).as_str(),
+                                ))
+                                .with_attribute(("source", source.as_str()))
+                                .write_empty()?;
+                        }
+                        let parse_error_severity =
+                            if result.fails_on_parse_errors(config) {
+                                "error"
+                            } else {
+                                "warning"
+                            };
+                        for parse_error in &errors.parse_errors {
+                            write_located_error(
+                                writer,
+                                parse_error,
+                                parse_error_severity,
+                                "pks.parse_error",
+                            )?;
+                        }
+                        for invalid_suppression in &errors.invalid_suppressions
+                        {
+                            write_located_error(
+                                writer,
+                                invalid_suppression,
+                                "error",
+                                "pks.invalid_suppression",
+                            )?;
+                        }
+                        Ok(())
+                    })?;
+            }
+            Ok(())
+        })?;
+    writeln!(writer.get_mut())?;
+    Ok(())
+}
@@ -6,7 +6,9 @@ use clap_derive::Args;
 use std::path::PathBuf;
 use tracing::debug;
 
+use super::dependencies::Direction;
 use super::logger::install_logger;
+use super::reference_query::ReferenceFilter;
 
 /// Error returned when violations are found during check.
 ///
@@ -81,9 +83,36 @@ enum Command {
     #[clap(about = "Just saying hi")]
     Greet,
 
+    #[clap(
+        about = "Write a packwerk.yml and root package.yml for an app that does not use packs yet"
+    )]
+    Init,
+
     #[clap(about = "Create a new pack")]
     Create { name: String },
 
+    #[clap(
+        about = "Move files into another pack, keeping their place in its directory structure"
+    )]
+    Move {
+        /// The file or directory to move
+        path: String,
+
+        /// The pack to move it into
+        destination: String,
+    },
+
+    #[clap(
+        about = "Rename a pack, moving its directory and updating every reference to it"
+    )]
+    RenamePack {
+        /// The current name of the pack
+        old: String,
+
+        /// The new name of the pack, e.g. packs/new_name
+        new: String,
+    },
+
     #[clap(about = "Look for violations in the codebase")]
     Check {
         /// Ignore recorded violations when reporting violations
@@ -93,6 +122,18 @@ enum Command {
         #[arg(short, long, default_value = "packwerk")]
         output_format: OutputFormat,
 
+        /// Keep running and re-check whenever files change
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Fail when a file cannot be parsed, since its violations cannot be found
+        #[arg(long)]
+        fail_on_parse_errors: bool,
+
+        /// Only check files changed since this git ref (e.g. origin/main), and only report violations introduced since then
+        #[arg(long, value_name = "GIT_REF", conflicts_with = "watch")]
+        since: Option<String>,
+
         files: Vec<String>,
     },
 
@@ -105,6 +146,11 @@ enum Command {
         file: String,
     },
 
+    #[clap(
+        about = "Run a language server that reports violations as diagnostics in your editor"
+    )]
+    Lsp,
+
     #[clap(
         about = "Update package_todo.yml files with the current violations",
         alias = "update-todo"
@@ -112,7 +158,11 @@ enum Command {
     Update,
 
     #[clap(about = "Look for validation errors in the codebase")]
-    Validate,
+    Validate {
+        /// Count the references behind each dependency in a cycle (parses every included file)
+        #[arg(long)]
+        explain_cycles: bool,
+    },
 
     #[clap(about = "Add a dependency from one pack to another")]
     AddDependency {
@@ -153,6 +203,12 @@ enum Command {
     )]
     DeleteCache,
 
+    #[clap(about = "Inspect, maintain or share the cache")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+
     #[clap(
         about = "List packs based on configuration in packwerk.yml (for debugging purposes)"
     )]
@@ -162,6 +218,59 @@ enum Command {
     ListPackDependencies {
         /// The pack that is depended on
         pack: String,
+
+        /// List the packs this pack depends on instead
+        #[arg(long)]
+        reverse: bool,
+
+        /// Follow declared dependencies transitively and print a tree
+        #[arg(long)]
+        transitive: bool,
+
+        /// Limit the tree to N levels (implies --transitive)
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+    },
+
+    #[clap(
+        about = "Print the shortest chain of declared dependencies from one pack to another"
+    )]
+    Path {
+        /// The pack to start from
+        from_pack: String,
+
+        /// The pack to reach
+        to_pack: String,
+    },
+
+    #[clap(about = "Export the pack dependency graph")]
+    Graph(GraphArgs),
+
+    #[clap(
+        about = "Summarize recorded and new violations per pack, checker, owner and defining pack"
+    )]
+    Stats {
+        #[arg(short, long, default_value = "table")]
+        format: StatsFormat,
+    },
+
+    #[clap(
+        about = "List the references between packs, and which of them are violations"
+    )]
+    References(ReferencesArgs),
+
+    #[clap(
+        about = "Explain why one pack depends on another, listing the references behind it"
+    )]
+    Why {
+        /// The pack that depends on another pack
+        from_pack: String,
+
+        /// The pack that is depended on
+        to_pack: String,
+
+        #[arg(short, long, default_value = "text")]
+        format: ReferencesFormat,
     },
 
     #[clap(
@@ -173,6 +282,41 @@ enum Command {
         about = "List the constants that packs sees and where it sees them (for debugging purposes)"
     )]
     ListDefinitions(ListDefinitionsArgs),
+
+    #[clap(
+        about = "List constants in public folders that are not referenced from any other pack"
+    )]
+    ListUnusedPublicApi,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    #[clap(about = "Write the cache directory to a single .tar.gz archive")]
+    Export {
+        /// The archive to write
+        archive: PathBuf,
+    },
+
+    #[clap(
+        about = "Replace the cache with the entries in an archive written by `pks cache export`"
+    )]
+    Import {
+        /// The archive to read
+        archive: PathBuf,
+    },
+
+    #[clap(
+        about = "Show the number and size of cache entries, and the hit rate of the last run"
+    )]
+    Stats,
+
+    #[clap(about = "Read every cache entry and report the corrupt ones")]
+    Verify,
+
+    #[clap(
+        about = "Remove cache entries for files that are no longer included"
+    )]
+    Prune,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
@@ -180,6 +324,34 @@ pub enum OutputFormat {
     Packwerk,
     CSV,
     JSON,
+    Sarif,
+    Junit,
+    Checkstyle,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    Table,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReferencesFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorBy {
+    Layer,
+    Owner,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -200,6 +372,42 @@ struct ListDefinitionsArgs {
     ambiguous: bool,
 }
 
+#[derive(Debug, Args)]
+struct GraphArgs {
+    #[arg(short, long, default_value = "dot")]
+    format: GraphFormat,
+
+    /// Overlay the references found in the code, marking each dependency as used, unused or undeclared
+    #[arg(short, long)]
+    references: bool,
+
+    /// Color packs by their layer or owner
+    #[arg(long, default_value = "layer")]
+    color_by: ColorBy,
+}
+
+#[derive(Debug, Args)]
+struct ReferencesArgs {
+    /// Only references from this pack
+    #[arg(long)]
+    from_pack: Option<String>,
+
+    /// Only references to constants defined in this pack
+    #[arg(long)]
+    to_pack: Option<String>,
+
+    /// Only references to this constant, e.g. ::Foo::Bar
+    #[arg(long)]
+    constant: Option<String>,
+
+    /// Only references in this file or directory (can be repeated)
+    #[arg(long)]
+    file: Vec<String>,
+
+    #[arg(short, long, default_value = "text")]
+    format: ReferencesFormat,
+}
+
 #[derive(Debug, Args)]
 struct ExposeMonkeyPatchesArgs {
     /// An absolute path to the directory containing Ruby source code (for extracting definitions from Ruby stdlib)
@@ -229,6 +437,10 @@ pub fn run() -> anyhow::Result<()> {
 
     install_logger(args.debug);
 
+    if let Command::Init = args.command {
+        return packs::init(&absolute_root);
+    }
+
     let mut configuration = packs::configuration::get(&absolute_root)?;
 
     if args.print_files {
@@ -270,25 +482,96 @@ pub fn run() -> anyhow::Result<()> {
             packs::greet();
             Ok(())
         }
+        Command::Cache { command } => match command {
+            CacheCommand::Export { archive } => {
+                packs::export_cache(&configuration, &archive)
+            }
+            CacheCommand::Import { archive } => {
+                packs::import_cache(&configuration, &archive)
+            }
+            CacheCommand::Stats => packs::cache_stats(&configuration),
+            CacheCommand::Verify => packs::verify_cache(&configuration),
+            CacheCommand::Prune => packs::prune_cache(&configuration),
+        },
         Command::ListPacks => {
             packs::list(configuration);
             Ok(())
         }
-        Command::ListPackDependencies { pack } => {
-            packs::list_dependencies(&configuration, pack)
+        Command::ListPackDependencies {
+            pack,
+            reverse,
+            transitive,
+            depth,
+        } => {
+            let direction = if reverse {
+                Direction::Dependencies
+            } else {
+                Direction::Dependents
+            };
+            if transitive || depth.is_some() {
+                packs::list_transitive_dependencies(
+                    &configuration,
+                    pack,
+                    direction,
+                    depth,
+                )
+            } else {
+                packs::list_dependencies(&configuration, pack, direction)
+            }
+        }
+        Command::Path { from_pack, to_pack } => {
+            packs::path(&configuration, from_pack, to_pack)
         }
         Command::AddDependency { from, to } => {
             packs::add_dependency(&configuration, from, to)
         }
         Command::ListIncludedFiles => packs::list_included_files(configuration),
+        Command::Graph(args) => packs::graph(
+            &configuration,
+            args.format,
+            args.references,
+            args.color_by,
+        ),
+        Command::Stats { format } => packs::stats(&configuration, format),
+        Command::Why {
+            from_pack,
+            to_pack,
+            format,
+        } => packs::why(&configuration, from_pack, to_pack, format),
+        Command::References(args) => packs::references(
+            &configuration,
+            ReferenceFilter {
+                from_pack: args.from_pack,
+                to_pack: args.to_pack,
+                constant: args.constant,
+                files: args.file,
+            },
+            args.format,
+        ),
         Command::Check {
             ignore_recorded_violations,
             output_format,
+            watch,
+            fail_on_parse_errors,
+            since,
             files,
         } => {
             configuration.ignore_recorded_violations =
                 ignore_recorded_violations;
-            packs::check(&configuration, output_format, args.color, files)
+            configuration.fail_on_parse_errors |= fail_on_parse_errors;
+            if watch {
+                packs::watch(configuration, output_format, args.color, files)
+            } else if let Some(base) = since {
+                packs::check_since(
+                    &configuration,
+                    &base,
+                    output_format,
+                    args.color,
+                    files,
+                )
+            } else {
+                packs::check(&configuration, output_format, args.color, files)
+            }
         }
         Command::CheckContents {
             ignore_recorded_violations,
@@ -306,8 +589,10 @@ pub fn run() -> anyhow::Result<()> {
                 vec![file],
             )
         }
+        Command::Lsp => packs::lsp(configuration),
         Command::Update => packs::update(&configuration),
-        Command::Validate => {
+        Command::Validate { explain_cycles } => {
+            configuration.explain_cycles = explain_cycles;
             packs::validate(&configuration)
             // Err("💡 Please use `packs check` to detect dependency cycles and run other configuration validations".into())
         }
@@ -325,6 +610,9 @@ pub fn run() -> anyhow::Result<()> {
             let ambiguous = args.ambiguous;
             packs::list_definitions(&configuration, ambiguous)
         }
+        Command::ListUnusedPublicApi => {
+            packs::list_unused_public_api(&configuration)
+        }
         Command::ExposeMonkeyPatches(args) => packs::expose_monkey_patches(
             &configuration,
             &args.rubydir,
@@ -333,6 +621,15 @@ pub fn run() -> anyhow::Result<()> {
         Command::LintPackageYmlFiles => {
             packs::lint_package_yml_files(&configuration)
         }
+        Command::Init => {
+            unreachable!("handled before loading the configuration")
+        }
         Command::Create { name } => packs::create(&configuration, name),
+        Command::Move { path, destination } => {
+            packs::move_to_pack(&configuration, path, destination, args.color)
+        }
+        Command::RenamePack { old, new } => {
+            packs::rename_pack(&configuration, old, new)
+        }
     }
 }
@@ -1,10 +1,12 @@
 use super::caching::{
-    cache::Cache, create_cache_dir_idempotently, noop_cache::NoopCache,
-    per_file_cache::PerFileCache,
+    cache::Cache, create_cache_dir_idempotently, invalidate_stale_cache,
+    noop_cache::NoopCache, per_file_cache::PerFileCache, CACHE_FORMAT_VERSION,
 };
 use super::checker::layer::Layers;
 use super::checker_configuration::{CheckerConfiguration, CheckerType};
-use super::file_utils::user_inputted_paths_to_absolute_filepaths;
+use super::file_utils::{
+    file_content_digest, user_inputted_paths_to_absolute_filepaths,
+};
 
 use super::raw_configuration::CheckerOverrides;
 use super::{
@@ -21,6 +23,8 @@ use std::{
 use tracing::debug;
 use walk_directory::walk_directory;
 
+/// Everything pks knows about an application before parsing any Ruby: its
+/// packwerk.yml settings, packs and included files.
 pub struct Configuration {
     pub included_files: HashSet<PathBuf>,
     pub absolute_root: PathBuf,
@@ -41,6 +45,10 @@ pub struct Configuration {
     pub print_files: bool,
     pub packs_first_mode: bool,
     pub ignore_recorded_violations: bool,
+    pub fail_on_parse_errors: bool,
+    /// Whether `validate` counts the references behind each dependency in a
+    /// cycle, which means parsing every included file
+    pub explain_cycles: bool,
     pub disable_enforce_dependencies: bool,
     pub disable_enforce_folder_privacy: bool,
     pub disable_enforce_layers: bool,
@@ -70,6 +78,10 @@ impl Configuration {
 
     pub(crate) fn get_cache(&self) -> Box<dyn Cache + Send + Sync> {
         if self.cache_enabled {
+            invalidate_stale_cache(
+                &self.cache_directory,
+                &self.cache_fingerprint(),
+            );
             let cache_dir = if self.experimental_parser {
                 self.cache_directory.join("experimental")
             } else {
@@ -78,15 +90,41 @@ impl Configuration {
 
             create_cache_dir_idempotently(&cache_dir);
 
-            Box::new(PerFileCache { cache_dir })
+            Box::new(PerFileCache::new(cache_dir, self.absolute_root.clone()))
         } else {
             Box::new(NoopCache {})
         }
     }
 
+    /// A digest of everything besides a file's contents that changes what
+    /// parsing it produces, so cached results from another pks version or
+    /// configuration are not reused.
+    pub(crate) fn cache_fingerprint(&self) -> String {
+        let mut autoload_roots: Vec<_> = self.autoload_roots.iter().collect();
+        autoload_roots.sort();
+        let inflections_digest =
+            file_content_digest(&self.inflections_path).unwrap_or_default();
+
+        let fingerprint = format!(
+            "version: {}\ncache_format: {}\ncustom_associations: {:?}\nautoload_roots: {:?}\ninflections: {}\n",
+            env!("CARGO_PKG_VERSION"),
+            CACHE_FORMAT_VERSION,
+            self.custom_associations,
+            autoload_roots,
+            inflections_digest,
+        );
+        format!("{:x}", md5::compute(fingerprint))
+    }
+
     pub(crate) fn constant_resolver_configuration(
         &self,
     ) -> ConstantResolverConfiguration<'_> {
+        if self.cache_enabled {
+            invalidate_stale_cache(
+                &self.cache_directory,
+                &self.cache_fingerprint(),
+            );
+        }
         ConstantResolverConfiguration {
             absolute_root: &self.absolute_root,
             cache_directory: &self.cache_directory,
@@ -95,6 +133,43 @@ impl Configuration {
             inflections_path: &self.inflections_path,
         }
     }
+
+    /// Re-reads the configuration from disk (e.g. after a package.yml
+    /// changed), keeping the overrides that were applied from the command line.
+    pub(crate) fn reload(&self) -> anyhow::Result<Configuration> {
+        let mut configuration = get(&self.absolute_root)?;
+        configuration.print_files = self.print_files;
+        configuration.stdin_file_path = self.stdin_file_path.clone();
+        configuration.ignore_recorded_violations =
+            self.ignore_recorded_violations;
+        configuration.fail_on_parse_errors |= self.fail_on_parse_errors;
+        configuration.experimental_parser |= self.experimental_parser;
+        configuration.cache_enabled &= self.cache_enabled;
+        configuration.disable_enforce_dependencies =
+            self.disable_enforce_dependencies;
+        configuration.disable_enforce_folder_privacy =
+            self.disable_enforce_folder_privacy;
+        configuration.disable_enforce_layers = self.disable_enforce_layers;
+        configuration.disable_enforce_privacy = self.disable_enforce_privacy;
+        configuration.disable_enforce_visibility =
+            self.disable_enforce_visibility;
+        Ok(configuration)
+    }
+}
+
+// Changing one of these changes which packs exist, what they allow or what
+// has been recorded, so long-running commands reload the configuration.
+const CONFIGURATION_FILE_NAMES: [&str; 4] = [
+    "package.yml",
+    "package_todo.yml",
+    "packwerk.yml",
+    "packs.yml",
+];
+
+pub(crate) fn is_configuration_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CONFIGURATION_FILE_NAMES.contains(&name))
 }
 
 pub(crate) fn get(absolute_root: &Path) -> anyhow::Result<Configuration> {
@@ -133,6 +208,7 @@ pub(crate) fn from_raw(
     let autoload_roots: HashMap<PathBuf, String> = raw_config.autoload_roots;
 
     let packs_first_mode = raw_config.packs_first_mode;
+    let fail_on_parse_errors = raw_config.fail_on_parse_errors;
 
     let inflections_path = absolute_root.join(
         raw_config
@@ -175,6 +251,8 @@ pub(crate) fn from_raw(
         print_files: false,
         packs_first_mode,
         ignore_recorded_violations: false,
+        fail_on_parse_errors,
+        explain_cycles: false,
         disable_enforce_dependencies: false,
         disable_enforce_folder_privacy: false,
         disable_enforce_layers: false,
@@ -114,6 +114,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             },
             Reference {
                 constant_name: String::from("::Bar::BarChild"),
@@ -126,6 +127,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                suppressions: vec![],
             },
             Reference {
                 constant_name: String::from("::BarChild"),
@@ -141,6 +143,7 @@ mod tests {
                     line: 33,
                     column: 1,
                 },
+                suppressions: vec![],
             },
             Reference {
                 constant_name: String::from("::Bar"),
@@ -156,6 +159,7 @@ mod tests {
                     line: 53,
                     column: 1,
                 },
+                suppressions: vec![],
             },
         ]
     }
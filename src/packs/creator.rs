@@ -1,7 +1,11 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
 use anyhow::Context;
 
 use crate::packs::{
     pack::{write_pack_to_disk, Pack},
+    raw_configuration::RawConfiguration,
     PackageTodo,
 };
 
@@ -45,11 +49,11 @@ pub fn create(
         new_pack_path.join("app/services/").join(pack_name),
     )
     .context(format!("failed to create app/services/{}", name))?;
-    if is_rails(configuration) {
+    if is_rails(&configuration.absolute_root) {
         std::fs::create_dir_all(new_pack_path.join("app/controllers/"))
             .context("failed to create app/controllers")?;
     }
-    if is_rspec(configuration) {
+    if is_rspec(&configuration.absolute_root) {
         std::fs::create_dir_all(new_pack_path.join("spec"))
             .context("failed to create spec")?;
     }
@@ -88,16 +92,174 @@ See https://github.com/rubyatscale/pks#readme for more info!",
     }
 }
 
-fn is_rails(configuration: &Configuration) -> bool {
-    gemfile_contains(configuration, "rails")
+/// Writes a packwerk.yml and a root package.yml for the application at
+/// `absolute_root`, leaving alone whichever already exists.
+pub fn init(
+    absolute_root: &Path,
+) -> anyhow::Result<Vec<(&'static str, CreateResult)>> {
+    let mut results = vec![];
+
+    let existing_config = ["packwerk.yml", "packs.yml"]
+        .into_iter()
+        .find(|name| absolute_root.join(name).exists());
+    match existing_config {
+        Some(name) => results.push((name, CreateResult::AlreadyExists)),
+        None => {
+            std::fs::write(
+                absolute_root.join("packwerk.yml"),
+                packwerk_yml(absolute_root)?,
+            )
+            .context("Failed to write packwerk.yml")?;
+            results.push(("packwerk.yml", CreateResult::Success));
+        }
+    }
+
+    let root_package_yml = absolute_root.join("package.yml");
+    if root_package_yml.exists() {
+        results.push(("package.yml", CreateResult::AlreadyExists));
+    } else {
+        let root_pack = Pack::from_contents(
+            &root_package_yml,
+            absolute_root,
+            ROOT_PACKAGE_YML_CONTENTS,
+            PackageTodo::default(),
+        )?;
+        write_pack_to_disk(&root_pack)?;
+        results.push(("package.yml", CreateResult::Success));
+    }
+
+    Ok(results)
+}
+
+const ROOT_PACKAGE_YML_CONTENTS: &str = "enforce_dependencies: true";
+
+// Directories that hold packs by convention, used when no pack exists yet
+const CONVENTIONAL_PACK_DIRECTORIES: [&str; 3] =
+    ["packs", "components", "engines"];
+
+// Directories never searched for packs
+const SKIPPED_DIRECTORIES: [&str; 5] =
+    ["node_modules", "tmp", "vendor", "log", "public"];
+
+fn packwerk_yml(absolute_root: &Path) -> anyhow::Result<String> {
+    let defaults = RawConfiguration::default();
+
+    let mut include = defaults.include.clone();
+    if !is_rails(absolute_root) {
+        // ERB templates come with Rails views
+        include.retain(|pattern| !pattern.ends_with(".erb"));
+    }
+    let mut exclude = defaults.exclude.clone();
+    if is_rspec(absolute_root) && absolute_root.join("spec/fixtures").is_dir() {
+        exclude.push(String::from("spec/fixtures/**/*"));
+    }
+
+    Ok(format!(
+        "# See: Setting up the configuration file
+# https://github.com/Shopify/packwerk/blob/main/USAGE.md#setting-up-the-configuration-file
+
+# List of patterns for folder paths to include
+include:
+{}
+# List of patterns for folder paths to exclude
+exclude:
+{}
+# Patterns to find package configuration files
+package_paths:
+{}
+# Whether or not you want the cache enabled
+cache: {}
+
+# Where you want the cache to be stored
+cache_directory: \"{}\"
+
+# Architecture layers, from highest to lowest. Packs set their `layer` in
+# package.yml, and packs with `enforce_layers: true` may only depend on packs
+# in the same layer or below.
+# layers:
+# - product
+# - utilities
+",
+        yaml_list(&include),
+        yaml_list(&exclude),
+        yaml_list(&detect_package_paths(absolute_root)?),
+        defaults.cache,
+        defaults.cache_directory,
+    ))
+}
+
+fn yaml_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("- \"{}\"\n", item))
+        .collect()
+}
+
+// A glob for each depth at which package.yml files are found under each
+// top-level directory, e.g. `packs/*` and `packs/*/*`.
+fn detect_package_paths(absolute_root: &Path) -> anyhow::Result<Vec<String>> {
+    let mut package_paths = BTreeSet::new();
+    for entry in std::fs::read_dir(absolute_root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.file_type()?.is_dir()
+            || name.starts_with('.')
+            || SKIPPED_DIRECTORIES.contains(&name.as_str())
+        {
+            continue;
+        }
+        for depth in package_yml_depths(&entry.path(), 1)? {
+            package_paths.insert(format!("{}{}", name, "/*".repeat(depth)));
+        }
+    }
+
+    if package_paths.is_empty() {
+        for name in CONVENTIONAL_PACK_DIRECTORIES {
+            if absolute_root.join(name).is_dir() {
+                package_paths.insert(format!("{}/*", name));
+            }
+        }
+    }
+    if package_paths.is_empty() {
+        package_paths.insert(String::from("packs/*"));
+    }
+    Ok(package_paths.into_iter().collect())
+}
+
+// How deep below `directory` its package.yml files are, up to a few levels
+fn package_yml_depths(
+    directory: &Path,
+    depth: usize,
+) -> anyhow::Result<BTreeSet<usize>> {
+    let mut depths = BTreeSet::new();
+    if depth > 3 {
+        return Ok(depths);
+    }
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir()
+            || entry.file_name().to_string_lossy().starts_with('.')
+        {
+            continue;
+        }
+        if entry.path().join("package.yml").exists() {
+            depths.insert(depth);
+        }
+        depths.extend(package_yml_depths(&entry.path(), depth + 1)?);
+    }
+    Ok(depths)
+}
+
+fn is_rails(absolute_root: &Path) -> bool {
+    gemfile_contains(absolute_root, "rails")
 }
 
-fn is_rspec(configuration: &Configuration) -> bool {
-    gemfile_contains(configuration, "rspec")
+fn is_rspec(absolute_root: &Path) -> bool {
+    gemfile_contains(absolute_root, "rspec")
 }
 
-fn gemfile_contains(configuration: &Configuration, val: &str) -> bool {
-    match std::fs::read_to_string(configuration.absolute_root.join("Gemfile")) {
+fn gemfile_contains(absolute_root: &Path, val: &str) -> bool {
+    match std::fs::read_to_string(absolute_root.join("Gemfile")) {
         Ok(as_string) => as_string.contains(val),
         _ => false,
     }
@@ -35,8 +35,38 @@ pub fn write_csv<W: std::io::Write>(
         "Referencing Pack",
         "Defining Pack",
         "Message",
+        "Line",
+        "Column",
     ])?;
 
+    for parse_error in &result.parse_errors {
+        wtr.serialize((
+            "parse_error",
+            false,
+            &parse_error.file,
+            "",
+            "",
+            "",
+            &parse_error.message,
+            parse_error.line,
+            parse_error.column,
+        ))?;
+    }
+
+    for invalid_suppression in &result.invalid_suppressions {
+        wtr.serialize((
+            "invalid_suppression",
+            false,
+            &invalid_suppression.file,
+            "",
+            "",
+            "",
+            &invalid_suppression.message,
+            invalid_suppression.line,
+            invalid_suppression.column,
+        ))?;
+    }
+
     if !&result.reportable_violations.is_empty()
         || !&result.strict_mode_violations.is_empty()
     {
@@ -56,10 +86,24 @@ pub fn write_csv<W: std::io::Write>(
                 &identifier.referencing_pack_name,
                 &identifier.defining_pack_name,
                 &message,
+                violation.source_location.line,
+                violation.source_location.column,
             ))?;
         }
-    } else {
-        wtr.serialize(("No violations detected!", "", "", "", "", "", ""))?;
+    } else if result.parse_errors.is_empty()
+        && result.invalid_suppressions.is_empty()
+    {
+        wtr.serialize((
+            "No violations detected!",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ))?;
     }
     wtr.flush()?;
     Ok(())
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::Configuration;
 
@@ -12,20 +12,42 @@ pub struct Dependencies {
     pub implicit: HashMap<PackName, HashMap<ViolationType, ViolationCount>>,
 }
 
+/// Which way to follow dependencies from a pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The packs that depend on the pack
+    Dependents,
+    /// The packs the pack depends on
+    Dependencies,
+}
+
+/// The packs directly related to `pack_name`, through `dependencies` in
+/// package.yml (explicit) or violations in package_todo.yml (implicit).
 pub fn find_dependencies(
     configuration: &Configuration,
     pack_name: &str,
+    direction: Direction,
 ) -> anyhow::Result<Dependencies> {
     let pack = configuration.pack_set.for_pack(pack_name)?;
 
-    let mut public_dependencies: Vec<PackName> = configuration
-        .pack_set
-        .packs
-        .iter()
-        .filter(|p| p.name != pack.name && p.dependencies.contains(&pack.name))
-        .map(|p| p.name.clone())
-        .collect();
-    public_dependencies.sort();
+    let mut explicit_dependencies: Vec<PackName> = match direction {
+        Direction::Dependents => configuration
+            .pack_set
+            .packs
+            .iter()
+            .filter(|p| {
+                p.name != pack.name && p.dependencies.contains(&pack.name)
+            })
+            .map(|p| p.name.clone())
+            .collect(),
+        Direction::Dependencies => pack
+            .dependencies
+            .iter()
+            .filter(|name| *name != &pack.name)
+            .cloned()
+            .collect(),
+    };
+    explicit_dependencies.sort();
 
     let mut implicit_dependencies: HashMap<
         PackName,
@@ -33,33 +55,201 @@ pub fn find_dependencies(
     > = HashMap::new();
 
     for current_pack in &configuration.pack_set.packs {
-        if current_pack.name != pack.name {
-            for (violation_pack_name, violation_groups) in
-                &current_pack.package_todo.violations_by_defining_pack
-            {
-                if violation_pack_name == &pack.name {
-                    for violation_group in violation_groups.values() {
-                        let entry = implicit_dependencies
-                            .entry(current_pack.name.clone())
-                            .or_default();
-                        for violation_type in &violation_group.violation_types {
-                            entry
-                                .entry(violation_type.clone())
-                                .and_modify(|e| *e += 1)
-                                .or_insert(1);
-                        }
-                    }
+        if current_pack.name == pack.name {
+            continue;
+        }
+        let (referencing_pack, defining_pack_name) = match direction {
+            Direction::Dependents => (current_pack, &pack.name),
+            Direction::Dependencies => (pack, &current_pack.name),
+        };
+        if let Some(violation_groups) = referencing_pack
+            .package_todo
+            .violations_by_defining_pack
+            .get(defining_pack_name)
+        {
+            for violation_group in violation_groups.values() {
+                let entry = implicit_dependencies
+                    .entry(current_pack.name.clone())
+                    .or_default();
+                for violation_type in &violation_group.violation_types {
+                    entry
+                        .entry(violation_type.clone())
+                        .and_modify(|e| *e += 1)
+                        .or_insert(1);
                 }
             }
         }
     }
 
     Ok(Dependencies {
-        explicit: public_dependencies,
+        explicit: explicit_dependencies,
         implicit: implicit_dependencies,
     })
 }
 
+/// A pack and the packs reached from it, following declared dependencies.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DependencyTree {
+    pub name: PackName,
+    pub children: Vec<DependencyTree>,
+    /// Already expanded elsewhere in the tree, so its children are left out
+    pub repeated: bool,
+    /// Depends on a pack on the path to it, so its children are left out
+    pub cycle: bool,
+}
+
+impl DependencyTree {
+    /// The distinct packs in the tree, not counting the root.
+    pub fn pack_count(&self) -> usize {
+        fn collect<'a>(tree: &'a DependencyTree, names: &mut HashSet<&'a str>) {
+            for child in &tree.children {
+                names.insert(&child.name);
+                collect(child, names);
+            }
+        }
+        let mut names = HashSet::new();
+        collect(self, &mut names);
+        names.remove(self.name.as_str());
+        names.len()
+    }
+}
+
+/// The closure of declared dependencies from `pack_name`, as a tree,
+/// optionally limited to `depth` levels.
+pub fn find_transitive_dependencies(
+    configuration: &Configuration,
+    pack_name: &str,
+    direction: Direction,
+    depth: Option<usize>,
+) -> anyhow::Result<DependencyTree> {
+    let pack = configuration.pack_set.for_pack(pack_name)?;
+    let graph = dependency_graph(configuration, direction)?;
+
+    let mut expanded = HashMap::new();
+    let mut path = vec![];
+    Ok(build_tree(
+        &graph,
+        &pack.name,
+        depth,
+        &mut expanded,
+        &mut path,
+    ))
+}
+
+fn build_tree<'a>(
+    graph: &'a HashMap<PackName, Vec<PackName>>,
+    name: &'a str,
+    depth: Option<usize>,
+    // Packs whose children were listed, with how many levels below them were
+    expanded: &mut HashMap<&'a str, usize>,
+    path: &mut Vec<&'a str>,
+) -> DependencyTree {
+    let mut tree = DependencyTree {
+        name: name.to_owned(),
+        children: vec![],
+        repeated: false,
+        cycle: false,
+    };
+    if path.contains(&name) {
+        tree.cycle = true;
+        return tree;
+    }
+    // A pack first reached close to the depth limit is listed again in full
+    // where it is reached with more levels to go
+    let levels = depth.unwrap_or(usize::MAX);
+    if expanded
+        .get(name)
+        .is_some_and(|&expanded| expanded >= levels)
+    {
+        tree.repeated = graph.get(name).is_some_and(|c| !c.is_empty());
+        return tree;
+    }
+    if levels == 0 {
+        return tree;
+    }
+    expanded.insert(name, levels);
+
+    path.push(name);
+    for child in graph.get(name).into_iter().flatten() {
+        tree.children.push(build_tree(
+            graph,
+            child,
+            depth.map(|d| d - 1),
+            expanded,
+            path,
+        ));
+    }
+    path.pop();
+    tree
+}
+
+/// The shortest chain of declared dependencies from `from` to `to`,
+/// including both, or `None` if `from` does not depend on `to`.
+pub fn shortest_path(
+    configuration: &Configuration,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Option<Vec<PackName>>> {
+    let from = &configuration.pack_set.for_pack(from)?.name;
+    let to = &configuration.pack_set.for_pack(to)?.name;
+    if from == to {
+        return Ok(Some(vec![from.clone()]));
+    }
+    let graph = dependency_graph(configuration, Direction::Dependencies)?;
+
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([from.as_str()]);
+    while let Some(name) = queue.pop_front() {
+        if name == to {
+            break;
+        }
+        for child in graph.get(name).into_iter().flatten() {
+            if child != from && !previous.contains_key(child.as_str()) {
+                previous.insert(child, name);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    let mut path = vec![to.as_str()];
+    while let Some(name) = previous.get(path[path.len() - 1]) {
+        path.push(name);
+        if name == from {
+            path.reverse();
+            return Ok(Some(path.into_iter().map(String::from).collect()));
+        }
+    }
+    Ok(None)
+}
+
+// Declared dependencies between packs, with sorted neighbours
+fn dependency_graph(
+    configuration: &Configuration,
+    direction: Direction,
+) -> anyhow::Result<HashMap<PackName, Vec<PackName>>> {
+    let mut graph: HashMap<PackName, Vec<PackName>> = HashMap::new();
+    for dependency in configuration
+        .pack_set
+        .all_pack_dependencies(configuration)?
+    {
+        let (from, to) = match direction {
+            Direction::Dependencies => {
+                (dependency.from_pack, dependency.to_pack)
+            }
+            Direction::Dependents => (dependency.to_pack, dependency.from_pack),
+        };
+        graph
+            .entry(from.name.clone())
+            .or_default()
+            .push(to.name.clone());
+    }
+    for neighbours in graph.values_mut() {
+        neighbours.sort();
+        neighbours.dedup();
+    }
+    Ok(graph)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::packs::configuration;
@@ -77,8 +267,12 @@ mod tests {
         )
         .unwrap();
 
-        let dependencies =
-            find_dependencies(&configuration, "packs/baz").unwrap();
+        let dependencies = find_dependencies(
+            &configuration,
+            "packs/baz",
+            Direction::Dependents,
+        )
+        .unwrap();
         assert_eq!(dependencies.explicit.len(), 1);
         assert!(dependencies.explicit.contains(&String::from("packs/foo")));
         assert_eq!(dependencies.implicit.len(), 0);
@@ -94,8 +288,12 @@ mod tests {
         )
         .unwrap();
 
-        let dependencies =
-            find_dependencies(&configuration, "packs/bar").unwrap();
+        let dependencies = find_dependencies(
+            &configuration,
+            "packs/bar",
+            Direction::Dependents,
+        )
+        .unwrap();
         assert_eq!(dependencies.explicit.len(), 0);
         assert_eq!(dependencies.implicit.len(), 1);
         assert_eq!(dependencies.implicit.get("packs/foo").unwrap().len(), 1);
@@ -109,4 +307,100 @@ mod tests {
             &1usize
         );
     }
+
+    #[test]
+    fn find_reverse_dependencies() {
+        let configuration = configuration::get(
+            PathBuf::from("tests/fixtures/contains_package_todo")
+                .canonicalize()
+                .expect("Could not canonicalize path")
+                .as_path(),
+        )
+        .unwrap();
+
+        let dependencies = find_dependencies(
+            &configuration,
+            "packs/foo",
+            Direction::Dependencies,
+        )
+        .unwrap();
+        assert_eq!(dependencies.explicit.len(), 0);
+        assert_eq!(
+            dependencies
+                .implicit
+                .get("packs/bar")
+                .unwrap()
+                .get("dependency")
+                .unwrap(),
+            &1usize
+        );
+    }
+
+    #[test]
+    fn transitive_dependencies_stop_at_cycles() {
+        let configuration = configuration::get(
+            PathBuf::from("tests/fixtures/app_with_dependency_cycles")
+                .canonicalize()
+                .expect("Could not canonicalize path")
+                .as_path(),
+        )
+        .unwrap();
+
+        let tree = find_transitive_dependencies(
+            &configuration,
+            "packs/foo",
+            Direction::Dependencies,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            tree,
+            DependencyTree {
+                name: String::from("packs/foo"),
+                children: vec![DependencyTree {
+                    name: String::from("packs/bar"),
+                    children: vec![DependencyTree {
+                        name: String::from("packs/foo"),
+                        children: vec![],
+                        repeated: false,
+                        cycle: true,
+                    }],
+                    repeated: false,
+                    cycle: false,
+                }],
+                repeated: false,
+                cycle: false,
+            }
+        );
+        assert_eq!(tree.pack_count(), 1);
+
+        let tree = find_transitive_dependencies(
+            &configuration,
+            "packs/foo",
+            Direction::Dependencies,
+            Some(1),
+        )
+        .unwrap();
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn shortest_path_between_packs() {
+        let configuration = configuration::get(
+            PathBuf::from("tests/fixtures/app_with_dependency_cycles")
+                .canonicalize()
+                .expect("Could not canonicalize path")
+                .as_path(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            shortest_path(&configuration, "packs/bar", "packs/foo").unwrap(),
+            Some(vec![String::from("packs/bar"), String::from("packs/foo")])
+        );
+        assert_eq!(
+            shortest_path(&configuration, "packs/foo", "packs/baz").unwrap(),
+            None
+        );
+    }
 }
@@ -0,0 +1,83 @@
+//! Thin wrappers around the local `git` executable, for `pks check --since`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+fn run(root: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let output = Command::new("git")
+        .current_dir(root)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Fails unless `base` names a commit.
+pub(crate) fn verify_ref(root: &Path, base: &str) -> anyhow::Result<()> {
+    let commit = format!("{}^{{commit}}", base);
+    run(root, &["rev-parse", "--verify", "--quiet", &commit])
+        .with_context(|| format!("`{}` is not a git commit", base))?;
+    Ok(())
+}
+
+/// Absolute paths of the files under `root` that differ from `base`,
+/// including uncommitted changes and untracked files. Deleted files are left
+/// out, since there is nothing left in them to check.
+pub(crate) fn changed_files(
+    root: &Path,
+    base: &str,
+) -> anyhow::Result<HashSet<PathBuf>> {
+    let changed = run(
+        root,
+        &[
+            "diff",
+            "--name-only",
+            "-z",
+            "--relative",
+            "--diff-filter=d",
+            base,
+            "--",
+        ],
+    )?;
+    let untracked =
+        run(root, &["ls-files", "-z", "--others", "--exclude-standard"])?;
+
+    Ok(changed
+        .split(|b| *b == 0)
+        .chain(untracked.split(|b| *b == 0))
+        .filter(|path| !path.is_empty())
+        .map(|path| root.join(String::from_utf8_lossy(path).as_ref()))
+        .collect())
+}
+
+/// The contents of `relative_path` (relative to `root`) at `base`, or `None`
+/// if the file did not exist there.
+pub(crate) fn file_at(
+    root: &Path,
+    base: &str,
+    relative_path: &Path,
+) -> anyhow::Result<Option<String>> {
+    let object = format!("{}:./{}", base, relative_path.display());
+    let exists = Command::new("git")
+        .current_dir(root)
+        .args(["cat-file", "-e", &object])
+        .output()
+        .context("Failed to run git")?
+        .status
+        .success();
+    if !exists {
+        return Ok(None);
+    }
+    let contents = run(root, &["show", &object])?;
+    Ok(Some(String::from_utf8_lossy(&contents).into_owned()))
+}
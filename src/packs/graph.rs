@@ -0,0 +1,378 @@
+//! Pack dependency graph export for `pks graph`.
+//!
+//! Builds the graph of declared dependencies between packs, optionally
+//! overlaid with the references actually found in the code, and renders it
+//! as Graphviz DOT, Mermaid or JSON.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::checker::get_pack_reference_counts;
+use super::cli::{ColorBy, GraphFormat};
+use super::pack::Pack;
+use super::Configuration;
+
+// Fill colors handed out to layers or owners in sorted order.
+const PALETTE: [&str; 10] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462",
+    "#b3de69", "#fccde5", "#d9d9d9", "#bc80bd",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// A dependency from package.yml, without reference information
+    Declared,
+    DeclaredAndUsed,
+    DeclaredButUnused,
+    UsedButUndeclared,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Node {
+    pub name: String,
+    pub layer: Option<String>,
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+pub fn build_graph(
+    configuration: &Configuration,
+    with_references: bool,
+) -> anyhow::Result<Graph> {
+    let declared: BTreeSet<(String, String)> = configuration
+        .pack_set
+        .all_pack_dependencies(configuration)?
+        .into_iter()
+        .map(|dep| (dep.from_pack.name.clone(), dep.to_pack.name.clone()))
+        .collect();
+
+    let edges = if with_references {
+        let reference_counts = get_pack_reference_counts(configuration)?;
+        overlay_references(&declared, &reference_counts)
+    } else {
+        declared
+            .into_iter()
+            .map(|(from, to)| Edge {
+                from,
+                to,
+                kind: EdgeKind::Declared,
+                references: None,
+            })
+            .collect()
+    };
+
+    let mut nodes: Vec<Node> =
+        configuration.pack_set.packs.iter().map(node_for).collect();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Graph { nodes, edges })
+}
+
+fn node_for(pack: &Pack) -> Node {
+    Node {
+        name: pack.name.clone(),
+        layer: pack.layer.clone(),
+        owner: pack.owner.clone(),
+    }
+}
+
+fn overlay_references(
+    declared: &BTreeSet<(String, String)>,
+    reference_counts: &HashMap<(String, String), usize>,
+) -> Vec<Edge> {
+    let mut edges: BTreeMap<(String, String), (EdgeKind, usize)> = declared
+        .iter()
+        .map(|key| {
+            let count = reference_counts.get(key).copied().unwrap_or(0);
+            let kind = if count > 0 {
+                EdgeKind::DeclaredAndUsed
+            } else {
+                EdgeKind::DeclaredButUnused
+            };
+            (key.clone(), (kind, count))
+        })
+        .collect();
+
+    for (key, count) in reference_counts {
+        let (from, to) = key;
+        if from != to && !declared.contains(key) {
+            edges.insert(key.clone(), (EdgeKind::UsedButUndeclared, *count));
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|((from, to), (kind, count))| Edge {
+            from,
+            to,
+            kind,
+            references: Some(count),
+        })
+        .collect()
+}
+
+impl Node {
+    fn group(&self, color_by: ColorBy) -> Option<&str> {
+        match color_by {
+            ColorBy::Layer => self.layer.as_deref(),
+            ColorBy::Owner => self.owner.as_deref(),
+        }
+    }
+}
+
+impl Graph {
+    // Assigns a palette color to each distinct layer or owner.
+    fn colors(&self, color_by: ColorBy) -> HashMap<&str, &'static str> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.group(color_by))
+            .collect::<BTreeSet<&str>>()
+            .into_iter()
+            .enumerate()
+            .map(|(i, group)| (group, PALETTE[i % PALETTE.len()]))
+            .collect()
+    }
+
+    pub fn render(
+        &self,
+        format: GraphFormat,
+        color_by: ColorBy,
+    ) -> anyhow::Result<String> {
+        match format {
+            GraphFormat::Dot => Ok(self.to_dot(color_by)),
+            GraphFormat::Mermaid => Ok(self.to_mermaid(color_by)),
+            GraphFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+
+    fn to_dot(&self, color_by: ColorBy) -> String {
+        let colors = self.colors(color_by);
+        let mut out = String::from("digraph packs {\n");
+        out.push_str("  node [shape=box];\n");
+        for node in &self.nodes {
+            let mut attributes = vec![];
+            if let Some(group) = node.group(color_by) {
+                attributes.push(format!(
+                    "style=filled, fillcolor=\"{}\", tooltip=\"{}\"",
+                    colors[group], group
+                ));
+            }
+            let _ = writeln!(
+                out,
+                "  \"{}\"{};",
+                node.name,
+                dot_attributes(&attributes)
+            );
+        }
+        for edge in &self.edges {
+            let mut attributes = vec![];
+            match edge.kind {
+                EdgeKind::Declared | EdgeKind::DeclaredAndUsed => {}
+                EdgeKind::DeclaredButUnused => {
+                    attributes.push("style=dashed, color=gray".to_string())
+                }
+                EdgeKind::UsedButUndeclared => {
+                    attributes.push("color=red".to_string())
+                }
+            }
+            if let Some(count) = edge.references {
+                attributes.push(format!("label=\"{}\"", count));
+            }
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\"{};",
+                edge.from,
+                edge.to,
+                dot_attributes(&attributes)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_mermaid(&self, color_by: ColorBy) -> String {
+        let colors = self.colors(color_by);
+        // Pack names contain characters mermaid does not allow in ids
+        let ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.name.as_str(), format!("n{}", i)))
+            .collect();
+
+        let mut out = String::from("graph LR\n");
+        for node in &self.nodes {
+            let _ = writeln!(
+                out,
+                "  {}[\"{}\"]",
+                ids[node.name.as_str()],
+                node.name
+            );
+        }
+        let mut undeclared_links = vec![];
+        for (i, edge) in self.edges.iter().enumerate() {
+            let arrow = match edge.kind {
+                EdgeKind::DeclaredButUnused => "-.->",
+                _ => "-->",
+            };
+            let label = edge
+                .references
+                .map(|count| format!("|{}|", count))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "  {} {}{} {}",
+                ids[edge.from.as_str()],
+                arrow,
+                label,
+                ids[edge.to.as_str()]
+            );
+            if edge.kind == EdgeKind::UsedButUndeclared {
+                undeclared_links.push(i.to_string());
+            }
+        }
+        for node in &self.nodes {
+            if let Some(group) = node.group(color_by) {
+                let _ = writeln!(
+                    out,
+                    "  style {} fill:{}",
+                    ids[node.name.as_str()],
+                    colors[group]
+                );
+            }
+        }
+        if !undeclared_links.is_empty() {
+            let _ = writeln!(
+                out,
+                "  linkStyle {} stroke:red",
+                undeclared_links.join(",")
+            );
+        }
+        out
+    }
+}
+
+fn dot_attributes(attributes: &[String]) -> String {
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, layer: Option<&str>) -> Node {
+        Node {
+            name: name.to_string(),
+            layer: layer.map(String::from),
+            owner: None,
+        }
+    }
+
+    fn edge(from: &str, to: &str, kind: EdgeKind, count: usize) -> Edge {
+        Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+            references: Some(count),
+        }
+    }
+
+    fn key(from: &str, to: &str) -> (String, String) {
+        (from.to_string(), to.to_string())
+    }
+
+    #[test]
+    fn test_overlay_references() {
+        let declared = BTreeSet::from([
+            key("packs/a", "packs/b"),
+            key("packs/a", "packs/c"),
+        ]);
+        let reference_counts = HashMap::from([
+            (key("packs/a", "packs/b"), 3),
+            (key("packs/b", "packs/c"), 1),
+            (key("packs/b", "packs/b"), 7),
+        ]);
+
+        assert_eq!(
+            overlay_references(&declared, &reference_counts),
+            vec![
+                edge("packs/a", "packs/b", EdgeKind::DeclaredAndUsed, 3),
+                edge("packs/a", "packs/c", EdgeKind::DeclaredButUnused, 0),
+                edge("packs/b", "packs/c", EdgeKind::UsedButUndeclared, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = Graph {
+            nodes: vec![
+                node("packs/a", Some("product")),
+                node("packs/b", None),
+            ],
+            edges: vec![
+                edge("packs/a", "packs/b", EdgeKind::DeclaredButUnused, 0),
+                edge("packs/b", "packs/a", EdgeKind::UsedButUndeclared, 2),
+            ],
+        };
+
+        assert_eq!(
+            graph.to_dot(ColorBy::Layer),
+            r##"digraph packs {
+  node [shape=box];
+  "packs/a" [style=filled, fillcolor="#8dd3c7", tooltip="product"];
+  "packs/b";
+  "packs/a" -> "packs/b" [style=dashed, color=gray, label="0"];
+  "packs/b" -> "packs/a" [color=red, label="2"];
+}
+"##
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let graph = Graph {
+            nodes: vec![
+                node("packs/a", Some("product")),
+                node("packs/b", None),
+            ],
+            edges: vec![
+                edge("packs/a", "packs/b", EdgeKind::DeclaredAndUsed, 4),
+                edge("packs/b", "packs/a", EdgeKind::UsedButUndeclared, 2),
+            ],
+        };
+
+        assert_eq!(
+            graph.to_mermaid(ColorBy::Layer),
+            r##"graph LR
+  n0["packs/a"]
+  n1["packs/b"]
+  n0 -->|4| n1
+  n1 -->|2| n0
+  style n0 fill:#8dd3c7
+  linkStyle 1 stroke:red
+"##
+        );
+    }
+}
@@ -1,13 +1,14 @@
 //! JSON output formatter for `pks check -o json`.
 //!
-//! Serializes check results (violations, stale TODOs, and summary) to JSON.
+//! Serializes check results (violations, stale TODOs, suppressed violations,
+//! invalid `# pks:` comments and summary) to JSON.
 //! See `schema/check-output.json` for the JSON Schema specification.
 
 use itertools::chain;
 use serde::Serialize;
 
 use super::checker::{
-    build_strict_violation_message, CheckAllResult, Violation,
+    build_strict_violation_message, CheckAllResult, ParseError, Violation,
 };
 use super::checker_configuration::CheckerType;
 use super::template::{build_violation_vars, expand};
@@ -17,6 +18,9 @@ use super::Configuration;
 struct JsonOutput<'a> {
     violations: Vec<JsonViolation<'a>>,
     stale_todos: Vec<JsonStaleTodo<'a>>,
+    parse_errors: Vec<JsonParseError<'a>>,
+    suppressed_violations: Vec<JsonSuppressedViolation<'a>>,
+    invalid_suppressions: Vec<JsonParseError<'a>>,
     summary: JsonSummary,
 }
 
@@ -42,17 +46,41 @@ struct JsonStaleTodo<'a> {
     defining_pack_name: &'a str,
 }
 
+#[derive(Serialize)]
+struct JsonParseError<'a> {
+    file: &'a str,
+    line: usize,
+    column: usize,
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonSuppressedViolation<'a> {
+    violation_type: &'a CheckerType,
+    file: &'a str,
+    line: usize,
+    column: usize,
+    constant_name: &'a str,
+    referencing_pack_name: &'a str,
+    defining_pack_name: &'a str,
+    reason: &'a str,
+}
+
 #[derive(Serialize)]
 struct JsonSummary {
     violation_count: usize,
     stale_todo_count: usize,
     strict_violation_count: usize,
+    parse_error_count: usize,
+    suppressed_violation_count: usize,
+    invalid_suppression_count: usize,
     success: bool,
 }
 
 /// Build message from violation using template expansion.
 /// For JSON, reference_location is cleared (location is in separate fields).
-fn build_message(v: &Violation, config: &Configuration) -> String {
+/// Also used by the language server, which reports the location itself.
+pub(crate) fn build_message(v: &Violation, config: &Configuration) -> String {
     if v.identifier.strict {
         build_strict_violation_message(&v.identifier)
     } else {
@@ -71,6 +99,24 @@ pub fn write_json<W: std::io::Write>(
     config: &Configuration,
     writer: W,
 ) -> anyhow::Result<()> {
+    serde_json::to_writer(writer, &build_output(result, config))?;
+    Ok(())
+}
+
+/// The same data as `write_json`, for callers that hand it on in-process
+/// (e.g. the Ruby extension) rather than writing it out.
+#[cfg_attr(not(feature = "ruby"), allow(dead_code))]
+pub(crate) fn to_value(
+    result: &CheckAllResult,
+    config: &Configuration,
+) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::to_value(build_output(result, config))?)
+}
+
+fn build_output<'a>(
+    result: &'a CheckAllResult,
+    config: &Configuration,
+) -> JsonOutput<'a> {
     let all_violations = chain!(
         &result.reportable_violations,
         &result.strict_mode_violations
@@ -104,24 +150,67 @@ pub fn write_json<W: std::io::Write>(
         })
         .collect();
 
+    let parse_errors: Vec<JsonParseError> =
+        result.parse_errors.iter().map(json_parse_error).collect();
+
+    let suppressed_violations: Vec<JsonSuppressedViolation> = result
+        .suppressed_violations
+        .iter()
+        .map(|s| JsonSuppressedViolation {
+            violation_type: &s.violation.identifier.violation_type,
+            file: &s.violation.identifier.file,
+            line: s.violation.source_location.line,
+            column: s.violation.source_location.column,
+            constant_name: &s.violation.identifier.constant_name,
+            referencing_pack_name: &s
+                .violation
+                .identifier
+                .referencing_pack_name,
+            defining_pack_name: &s.violation.identifier.defining_pack_name,
+            reason: &s.reason,
+        })
+        .collect();
+
     let violation_count = violations.len();
     let stale_todo_count = stale_todos.len();
     let strict_violation_count = result.strict_mode_violations.len();
+    let parse_error_count = parse_errors.len();
+    let suppressed_violation_count = suppressed_violations.len();
+    let invalid_suppressions: Vec<JsonParseError> = result
+        .invalid_suppressions
+        .iter()
+        .map(json_parse_error)
+        .collect();
+    let invalid_suppression_count = invalid_suppressions.len();
     let success = violation_count == 0
         && stale_todo_count == 0
-        && strict_violation_count == 0;
+        && strict_violation_count == 0
+        && invalid_suppression_count == 0
+        && !result.fails_on_parse_errors(config);
 
-    let output = JsonOutput {
+    JsonOutput {
         violations,
         stale_todos,
+        parse_errors,
+        suppressed_violations,
+        invalid_suppressions,
         summary: JsonSummary {
             violation_count,
             stale_todo_count,
             strict_violation_count,
+            parse_error_count,
+            suppressed_violation_count,
+            invalid_suppression_count,
             success,
         },
-    };
+    }
+}
 
-    serde_json::to_writer(writer, &output)?;
-    Ok(())
+fn json_parse_error(e: &ParseError) -> JsonParseError<'_> {
+    JsonParseError {
+        file: &e.file,
+        line: e.line,
+        column: e.column,
+        message: &e.message,
+    }
 }
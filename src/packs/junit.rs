@@ -0,0 +1,202 @@
+//! JUnit XML output formatter for `pks check -o junit`.
+//!
+//! Writes one test suite per referencing pack, with one failing test case
+//! per violation, so CI systems can render violations like test failures.
+//! Stale TODOs have no location in the code, so they are not reported.
+//! Parse errors go into a separate suite, as test errors rather than failures.
+//! Invalid `# pks:` comments go into another one, as failures.
+
+use std::collections::BTreeMap;
+
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+
+use super::checker::{CheckAllResult, ParseError, Violation};
+use super::json::build_message;
+use super::Configuration;
+
+const PARSE_ERRORS_SUITE: &str = "parse errors";
+const INVALID_SUPPRESSIONS_SUITE: &str = "invalid pks comments";
+
+fn write_test_case<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    v: &Violation,
+    config: &Configuration,
+) -> std::io::Result<()> {
+    let name = format!(
+        "{}:{}:{} {} {}",
+        v.identifier.file,
+        v.source_location.line,
+        v.source_location.column,
+        v.identifier.violation_type,
+        v.identifier.constant_name
+    );
+    let message = build_message(v, config);
+    writer
+        .create_element("testcase")
+        .with_attribute(("name", name.as_str()))
+        .with_attribute((
+            "classname",
+            v.identifier.referencing_pack_name.as_str(),
+        ))
+        .with_attribute(("file", v.identifier.file.as_str()))
+        .with_attribute(("line", v.source_location.line.to_string().as_str()))
+        .write_inner_content(|writer| {
+            writer
+                .create_element("failure")
+                .with_attribute((
+                    "type",
+                    v.identifier.violation_type.to_string().as_str(),
+                ))
+                .with_attribute(("message", message.as_str()))
+                .write_text_content(BytesText::new(&message))?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+fn write_parse_error<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    parse_error: &ParseError,
+) -> std::io::Result<()> {
+    write_located_test_case(
+        writer,
+        parse_error,
+        PARSE_ERRORS_SUITE,
+        "error",
+        "parse_error",
+    )
+}
+
+fn write_invalid_suppression<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    invalid_suppression: &ParseError,
+) -> std::io::Result<()> {
+    write_located_test_case(
+        writer,
+        invalid_suppression,
+        INVALID_SUPPRESSIONS_SUITE,
+        "failure",
+        "invalid_suppression",
+    )
+}
+
+// `element` is "error" or "failure", `kind` its type, e.g. "parse_error"
+fn write_located_test_case<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    error: &ParseError,
+    suite: &str,
+    element: &str,
+    kind: &str,
+) -> std::io::Result<()> {
+    let name = format!(
+        "{}:{}:{} {}",
+        error.file,
+        error.line,
+        error.column,
+        kind.replace('_', " ")
+    );
+    writer
+        .create_element("testcase")
+        .with_attribute(("name", name.as_str()))
+        .with_attribute(("classname", suite))
+        .with_attribute(("file", error.file.as_str()))
+        .with_attribute(("line", error.line.to_string().as_str()))
+        .write_inner_content(|writer| {
+            writer
+                .create_element(element)
+                .with_attribute(("type", kind))
+                .with_attribute(("message", error.message.as_str()))
+                .write_text_content(BytesText::new(&error.message))?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+pub fn write_junit<W: std::io::Write>(
+    result: &CheckAllResult,
+    config: &Configuration,
+    writer: W,
+) -> anyhow::Result<()> {
+    let violations = result.sorted_violations();
+    let mut violations_by_pack: BTreeMap<&str, Vec<&Violation>> =
+        BTreeMap::new();
+    for v in &violations {
+        violations_by_pack
+            .entry(&v.identifier.referencing_pack_name)
+            .or_default()
+            .push(v);
+    }
+
+    let mut writer = Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new(
+        "1.0",
+        Some("UTF-8"),
+        None,
+    )))?;
+    let parse_errors = &result.parse_errors;
+    let invalid_suppressions = &result.invalid_suppressions;
+    let total =
+        (violations.len() + parse_errors.len() + invalid_suppressions.len())
+            .to_string();
+    let failures = (violations.len() + invalid_suppressions.len()).to_string();
+    let errors = parse_errors.len().to_string();
+    let invalid_suppression_count = invalid_suppressions.len().to_string();
+    let mut testsuites = writer
+        .create_element("testsuites")
+        .with_attribute(("name", "pks check"))
+        .with_attribute(("tests", total.as_str()))
+        .with_attribute(("failures", failures.as_str()));
+    if !parse_errors.is_empty() {
+        testsuites = testsuites.with_attribute(("errors", errors.as_str()));
+    }
+    testsuites.write_inner_content(|writer| {
+        for (pack_name, violations) in &violations_by_pack {
+            let count = violations.len().to_string();
+            writer
+                .create_element("testsuite")
+                .with_attribute(("name", *pack_name))
+                .with_attribute(("tests", count.as_str()))
+                .with_attribute(("failures", count.as_str()))
+                .write_inner_content(|writer| {
+                    for v in violations {
+                        write_test_case(writer, v, config)?;
+                    }
+                    Ok(())
+                })?;
+        }
+        if !parse_errors.is_empty() {
+            writer
+                .create_element("testsuite")
+                .with_attribute(("name", PARSE_ERRORS_SUITE))
+                .with_attribute(("tests", errors.as_str()))
+                .with_attribute(("failures", "0"))
+                .with_attribute(("errors", errors.as_str()))
+                .write_inner_content(|writer| {
+                    for parse_error in parse_errors {
+                        write_parse_error(writer, parse_error)?;
+                    }
+                    Ok(())
+                })?;
+        }
+        if !invalid_suppressions.is_empty() {
+            writer
+                .create_element("testsuite")
+                .with_attribute(("name", INVALID_SUPPRESSIONS_SUITE))
+                .with_attribute(("tests", invalid_suppression_count.as_str()))
+                .with_attribute((
+                    "failures",
+                    invalid_suppression_count.as_str(),
+                ))
+                .write_inner_content(|writer| {
+                    for invalid_suppression in invalid_suppressions {
+                        write_invalid_suppression(writer, invalid_suppression)?;
+                    }
+                    Ok(())
+                })?;
+        }
+        Ok(())
+    })?;
+    writeln!(writer.get_mut())?;
+    Ok(())
+}
@@ -0,0 +1,330 @@
+//! Language server for `pks lsp`.
+//!
+//! Speaks the Language Server Protocol over stdio and publishes the
+//! violations in open Ruby and ERB buffers as diagnostics. Buffers are
+//! re-checked as they change, without waiting for them to be saved.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use lsp_server::{
+    Connection, ErrorCode, Message, Notification, Request, Response,
+};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    DidSaveTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, NumberOrString, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Url,
+};
+
+use super::checker::{
+    check_references, invalid_suppressions_for, parse_errors_for, ParseError,
+    Violation,
+};
+use super::configuration::{self, Configuration};
+use super::constant_resolver::ConstantResolver;
+use super::json::build_message;
+use super::parsing::process_file_contents;
+use super::reference_extractor::{get_constant_resolver, resolve_references};
+
+pub(crate) fn run(mut configuration: Configuration) -> anyhow::Result<()> {
+    // stdout carries the protocol, so nothing else may print to it.
+    configuration.print_files = false;
+
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
+        )),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    // The server owns the connection, so that dropping it once the client
+    // has shut us down lets the writer thread finish.
+    Server::new(connection, configuration)?.main_loop()?;
+
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server {
+    connection: Connection,
+    configuration: Configuration,
+    constant_resolver: Box<dyn ConstantResolver + Send + Sync>,
+    open_documents: HashMap<Url, String>,
+}
+
+impl Server {
+    fn new(
+        connection: Connection,
+        configuration: Configuration,
+    ) -> anyhow::Result<Self> {
+        let constant_resolver = get_constant_resolver(&configuration)?;
+        Ok(Self {
+            connection,
+            configuration,
+            constant_resolver,
+            open_documents: HashMap::new(),
+        })
+    }
+
+    fn main_loop(mut self) -> anyhow::Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => {
+                    self.handle_notification(notification)?;
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> anyhow::Result<()> {
+        let response = Response::new_err(
+            request.id,
+            ErrorCode::MethodNotFound as i32,
+            format!("Unsupported request: {}", request.method),
+        );
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> anyhow::Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.open_documents
+                    .insert(uri.clone(), params.text_document.text);
+                self.publish(&uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                // We ask for full syncs, so the last change holds the whole buffer.
+                if let Some(change) = params.content_changes.into_iter().last()
+                {
+                    self.open_documents.insert(uri.clone(), change.text);
+                }
+                self.publish(&uri)?;
+            }
+            DidSaveTextDocument::METHOD => {
+                let params: DidSaveTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                if is_configuration_file(&params.text_document.uri) {
+                    self.reload()?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.open_documents.remove(&uri);
+                self.send_diagnostics(uri, vec![])?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self) -> anyhow::Result<()> {
+        match self.configuration.reload() {
+            Ok(configuration) => {
+                self.constant_resolver = get_constant_resolver(&configuration)?;
+                self.configuration = configuration;
+            }
+            Err(err) => {
+                // Keep serving the last good configuration while the user
+                // is halfway through editing a yml file.
+                eprintln!("Failed to reload configuration: {:?}", err);
+                return Ok(());
+            }
+        }
+
+        let uris: Vec<Url> = self.open_documents.keys().cloned().collect();
+        for uri in uris {
+            self.publish(&uri)?;
+        }
+        Ok(())
+    }
+
+    fn publish(&self, uri: &Url) -> anyhow::Result<()> {
+        let diagnostics = match self.open_documents.get(uri) {
+            Some(contents) => match self.diagnostics_for(uri, contents) {
+                Ok(diagnostics) => diagnostics,
+                Err(err) => {
+                    eprintln!("Failed to check {}: {:?}", uri, err);
+                    vec![]
+                }
+            },
+            None => vec![],
+        };
+        self.send_diagnostics(uri.clone(), diagnostics)
+    }
+
+    fn diagnostics_for(
+        &self,
+        uri: &Url,
+        contents: &str,
+    ) -> anyhow::Result<Vec<Diagnostic>> {
+        let Some(path) = absolute_path_for(uri) else {
+            return Ok(vec![]);
+        };
+        if !self.configuration.included_files.contains(&path) {
+            return Ok(vec![]);
+        }
+
+        let processed_file = process_file_contents(
+            &path,
+            contents.to_owned(),
+            &self.configuration,
+        );
+        let processed_files = [processed_file];
+        let references = resolve_references(
+            &self.configuration,
+            self.constant_resolver.as_ref(),
+            &processed_files,
+        )?;
+        let result = check_references(
+            &self.configuration,
+            HashSet::from([path]),
+            &references,
+            parse_errors_for(&self.configuration, &processed_files),
+            invalid_suppressions_for(&self.configuration, &processed_files),
+        )?;
+
+        let diagnostics = result
+            .parse_errors
+            .iter()
+            .map(parse_error_diagnostic)
+            .chain(
+                result
+                    .invalid_suppressions
+                    .iter()
+                    .map(invalid_suppression_diagnostic),
+            )
+            .chain(
+                result
+                    .sorted_violations()
+                    .into_iter()
+                    .map(|v| self.diagnostic_for(v)),
+            )
+            .collect();
+        Ok(diagnostics)
+    }
+
+    fn diagnostic_for(&self, violation: &Violation) -> Diagnostic {
+        let position = Position {
+            line: violation.source_location.line.saturating_sub(1) as u32,
+            character: violation.source_location.column as u32,
+        };
+        Diagnostic {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(
+                violation.identifier.violation_type.to_string(),
+            )),
+            source: Some(String::from("pks")),
+            message: build_message(violation, &self.configuration),
+            ..Default::default()
+        }
+    }
+
+    fn send_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<Diagnostic>,
+    ) -> anyhow::Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        let notification =
+            Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
+
+// Syntax errors are warnings: they are not violations, but the violations
+// after them go unreported.
+fn parse_error_diagnostic(parse_error: &ParseError) -> Diagnostic {
+    let position = Position {
+        line: parse_error.line.saturating_sub(1) as u32,
+        character: parse_error.column as u32,
+    };
+    Diagnostic {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(String::from("parse_error"))),
+        source: Some(String::from("pks")),
+        message: format!(
+            "{} (pks cannot check the references in this file)",
+            parse_error.message
+        ),
+        ..Default::default()
+    }
+}
+
+// Unlike syntax errors, these fail `pks check`
+fn invalid_suppression_diagnostic(
+    invalid_suppression: &ParseError,
+) -> Diagnostic {
+    let position = Position {
+        line: invalid_suppression.line.saturating_sub(1) as u32,
+        character: invalid_suppression.column as u32,
+    };
+    Diagnostic {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(String::from("invalid_suppression"))),
+        source: Some(String::from("pks")),
+        message: invalid_suppression.message.clone(),
+        ..Default::default()
+    }
+}
+
+fn absolute_path_for(uri: &Url) -> Option<PathBuf> {
+    let path = uri.to_file_path().ok()?;
+    // Included files are canonical, but the editor may hand us a symlinked path.
+    Some(path.canonicalize().unwrap_or(path))
+}
+
+fn is_configuration_file(uri: &Url) -> bool {
+    uri.to_file_path()
+        .is_ok_and(|path| configuration::is_configuration_file(&path))
+}
@@ -0,0 +1,504 @@
+//! Moving files between packs for `pks move`, and whole packs for
+//! `pks rename-pack`.
+//!
+//! Files keep their place in the pack's directory structure, so
+//! `packs/foo/app/services/foo/bar.rb` moved to `packs/baz` becomes
+//! `packs/baz/app/services/foo/bar.rb`. Recorded violations follow the moved
+//! files into the right package_todo.yml files, and violations the move
+//! introduces are reported rather than recorded.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use super::checker::{find_all_violations, Violation, ViolationIdentifier};
+use super::checker_configuration::CheckerType;
+use super::file_utils::build_glob_set;
+use super::pack::{serialize_pack, EnforcementGlobsIgnore, Pack};
+use super::package_todo::{serialized_package_todos, write_violations_to_disk};
+use super::raw_configuration;
+use super::{Configuration, SourceLocation};
+
+pub struct MoveResult {
+    /// (from, to) for every moved file, relative to the root
+    pub moved_files: Vec<(String, String)>,
+    /// Violations that did not exist before the move
+    pub new_violations: Vec<Violation>,
+    /// Recorded violations that no longer occur after the move
+    pub resolved_violations: Vec<ViolationIdentifier>,
+}
+
+// What makes a violation the same across a move, where the referencing and
+// defining packs may change. Files are keyed by their path before the move.
+type MoveKey = (CheckerType, String, String);
+
+pub fn move_to_pack(
+    configuration: &Configuration,
+    path: &str,
+    destination: &str,
+) -> anyhow::Result<MoveResult> {
+    let root = &configuration.absolute_root;
+    let destination_pack = configuration
+        .pack_set
+        .for_pack(destination)
+        .context(format!("`{}` not found", destination))?;
+
+    let source: PathBuf = root.join(path).components().collect();
+    if !source.exists() {
+        bail!("`{}` does not exist", path);
+    }
+    let source_pack = owning_pack(configuration, &source)?;
+    let source_pack_path = pack_path(source_pack);
+    if source == source_pack_path {
+        bail!("`{}` is a pack, not a file or directory in one", path);
+    }
+    if source_pack.name == destination_pack.name {
+        bail!("`{}` already belongs to `{}`", path, destination_pack.name);
+    }
+
+    let mut moves: Vec<(PathBuf, PathBuf)> = vec![];
+    for file in files_under(&source)? {
+        if owning_pack(configuration, &file)?.name != source_pack.name {
+            bail!(
+                "`{}` contains files from another pack: {}",
+                path,
+                relative(root, &file)
+            );
+        }
+        let destination_file = pack_path(destination_pack)
+            .join(file.strip_prefix(&source_pack_path)?);
+        if destination_file.exists() {
+            bail!("`{}` already exists", relative(root, &destination_file));
+        }
+        moves.push((file, destination_file));
+    }
+    moves.sort();
+
+    let violations_before = find_all_violations(configuration)?;
+
+    for (from, to) in &moves {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).context(format!(
+                "failed to create {}",
+                relative(root, parent)
+            ))?;
+        }
+        fs::rename(from, to).context(format!(
+            "failed to move {} to {}",
+            relative(root, from),
+            relative(root, to)
+        ))?;
+    }
+    if source.is_dir() {
+        remove_empty_dirs(&source)?;
+    }
+
+    let moved_files: Vec<(String, String)> = moves
+        .iter()
+        .map(|(from, to)| (relative(root, from), relative(root, to)))
+        .collect();
+    let path_before: HashMap<&str, &str> = moved_files
+        .iter()
+        .map(|(from, to)| (to.as_str(), from.as_str()))
+        .collect();
+    let path_after: HashMap<&str, &str> = moved_files
+        .iter()
+        .map(|(from, to)| (from.as_str(), to.as_str()))
+        .collect();
+    let key = |identifier: &ViolationIdentifier| -> MoveKey {
+        let file = path_before
+            .get(identifier.file.as_str())
+            .copied()
+            .unwrap_or(&identifier.file);
+        (
+            identifier.violation_type.clone(),
+            file.to_string(),
+            identifier.constant_name.clone(),
+        )
+    };
+
+    let new_configuration = configuration.reload()?;
+    let violations_after = find_all_violations(&new_configuration)?;
+
+    let keys_before: HashSet<MoveKey> = violations_before
+        .iter()
+        .map(|violation| key(&violation.identifier))
+        .collect();
+    let keys_after: HashSet<MoveKey> = violations_after
+        .iter()
+        .map(|violation| key(&violation.identifier))
+        .collect();
+    let recorded = &configuration.pack_set.all_violations;
+    let recorded_keys: HashSet<MoveKey> = recorded.iter().map(key).collect();
+
+    // Recorded violations that still occur are recorded again, under the
+    // packs they now involve
+    let mut violations_to_record: HashSet<Violation> = violations_after
+        .iter()
+        .filter(|violation| recorded_keys.contains(&key(&violation.identifier)))
+        .cloned()
+        .collect();
+    let mut resolved_violations = vec![];
+    for identifier in recorded {
+        if keys_before.contains(&key(identifier)) {
+            if !keys_after.contains(&key(identifier)) {
+                resolved_violations.push(identifier.clone());
+            }
+        } else {
+            // Already stale before the move, so it is left for `pks check`
+            // to report, with its file renamed if it moved
+            violations_to_record.insert(recorded_violation(
+                ViolationIdentifier {
+                    file: path_after
+                        .get(identifier.file.as_str())
+                        .map(|file| file.to_string())
+                        .unwrap_or_else(|| identifier.file.clone()),
+                    ..identifier.clone()
+                },
+            ));
+        }
+    }
+    write_violations_to_disk(&new_configuration, violations_to_record);
+
+    let mut new_violations: Vec<Violation> = violations_after
+        .into_iter()
+        .filter(|violation| !keys_before.contains(&key(&violation.identifier)))
+        .collect();
+    new_violations.sort_by(|a, b| {
+        a.identifier
+            .file
+            .cmp(&b.identifier.file)
+            .then_with(|| a.source_location.line.cmp(&b.source_location.line))
+            .then_with(|| {
+                a.identifier.constant_name.cmp(&b.identifier.constant_name)
+            })
+    });
+    resolved_violations.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.constant_name.cmp(&b.constant_name))
+    });
+
+    Ok(MoveResult {
+        moved_files,
+        new_violations,
+        resolved_violations,
+    })
+}
+
+/// Moves the pack named `old` to `new` and rewrites every reference to its
+/// name. Packs nested inside it are renamed along with it. Returns the
+/// (old, new) names of the renamed packs.
+pub fn rename_pack(
+    configuration: &Configuration,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let root = &configuration.absolute_root;
+    let old_pack = configuration
+        .pack_set
+        .for_pack(old)
+        .context(format!("`{}` not found", old))?;
+    let old = old_pack.name.as_str();
+    let new = new.trim_end_matches('/');
+    if old == "." {
+        bail!("The root pack cannot be renamed");
+    }
+    if renamed(new, old, new).is_some() {
+        bail!("`{}` cannot be moved inside itself", old);
+    }
+    if root.join(new).exists() {
+        bail!("`{}` already exists", new);
+    }
+
+    // A pack outside of `package_paths` would not be found afterwards
+    let package_paths =
+        build_glob_set(&raw_configuration::get(root)?.package_paths);
+    for pack in &configuration.pack_set.packs {
+        if let Some(new_name) = renamed(&pack.name, old, new) {
+            if !package_paths.is_match(&new_name) {
+                bail!(
+                    "`{}` does not match any of the `package_paths` in packwerk.yml",
+                    new_name
+                );
+            }
+        }
+    }
+
+    // Every file is written before the pack is moved, so files inside the
+    // pack are written to where they are now.
+    let mut writes: Vec<(PathBuf, String)> = vec![];
+    let mut removals: Vec<PathBuf> = vec![];
+
+    let packwerk_yml = root.join("packwerk.yml");
+    if packwerk_yml.exists() {
+        let contents = fs::read_to_string(&packwerk_yml)?;
+        let new_contents = rename_packwerk_yml_paths(&contents, old, new);
+        if new_contents != contents {
+            writes.push((packwerk_yml, new_contents));
+        }
+    }
+
+    let rename =
+        |name: &String| renamed(name, old, new).unwrap_or(name.clone());
+    let mut renamed_packs = vec![];
+    for pack in &configuration.pack_set.packs {
+        let mut new_pack = pack.clone();
+        new_pack.dependencies = pack.dependencies.iter().map(rename).collect();
+        new_pack.ignored_dependencies =
+            pack.ignored_dependencies.iter().map(rename).collect();
+        new_pack.visible_to = pack
+            .visible_to
+            .as_ref()
+            .map(|visible_to| visible_to.iter().map(rename).collect());
+        new_pack.enforcement_globs_ignore =
+            pack.enforcement_globs_ignore.as_ref().map(|globs_ignores| {
+                globs_ignores
+                    .iter()
+                    .map(|globs_ignore| EnforcementGlobsIgnore {
+                        ignores: globs_ignore
+                            .ignores
+                            .iter()
+                            .map(|glob| {
+                                renamed_glob(glob, old, new)
+                                    .unwrap_or(glob.clone())
+                            })
+                            .collect(),
+                        ..globs_ignore.clone()
+                    })
+                    .collect()
+            });
+        new_pack.public_folder = pack.public_folder.as_ref().map(|folder| {
+            renamed(&folder.to_string_lossy(), old, new)
+                .map(PathBuf::from)
+                .unwrap_or(folder.clone())
+        });
+        // Renamed packs that reference no renamed pack are already on disk
+        if new_pack != *pack {
+            writes.push((pack.yml.clone(), serialize_pack(&new_pack)?));
+        }
+        if let Some(new_name) = renamed(&pack.name, old, new) {
+            renamed_packs.push((pack.name.clone(), new_name));
+        }
+    }
+    renamed_packs.sort();
+
+    let recorded = &configuration.pack_set.all_violations;
+    let renamed_violations: HashSet<ViolationIdentifier> = recorded
+        .iter()
+        .map(|identifier| ViolationIdentifier {
+            file: rename(&identifier.file),
+            referencing_pack_name: rename(&identifier.referencing_pack_name),
+            defining_pack_name: rename(&identifier.defining_pack_name),
+            ..identifier.clone()
+        })
+        .collect();
+    if &renamed_violations != recorded {
+        let package_todos = serialized_package_todos(
+            renamed_violations
+                .into_iter()
+                .map(recorded_violation)
+                .collect(),
+            configuration.packs_first_mode,
+        );
+        for pack in &configuration.pack_set.packs {
+            let package_todo_yml = pack_path(pack).join("package_todo.yml");
+            match package_todos.get(&rename(&pack.name)) {
+                Some(contents) => {
+                    writes.push((package_todo_yml, contents.clone()))
+                }
+                None if package_todo_yml.exists() => {
+                    removals.push(package_todo_yml)
+                }
+                None => {}
+            }
+        }
+    }
+
+    for (path, contents) in writes {
+        fs::write(&path, contents)
+            .context(format!("Failed to write {}", relative(root, &path)))?;
+    }
+    for path in removals {
+        fs::remove_file(&path)
+            .context(format!("Failed to remove {}", relative(root, &path)))?;
+    }
+
+    let new_path = root.join(new);
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)
+            .context(format!("failed to create {}", relative(root, parent)))?;
+    }
+    fs::rename(pack_path(old_pack), &new_path)
+        .context(format!("failed to move {} to {}", old, new))?;
+
+    Ok(renamed_packs)
+}
+
+// `path` with the `old` pack path replaced by `new`, if it is in that pack.
+fn renamed(path: &str, old: &str, new: &str) -> Option<String> {
+    let rest = path.strip_prefix(old)?;
+    if rest.is_empty() || rest.starts_with('/') {
+        Some(format!("{}{}", new, rest))
+    } else {
+        None
+    }
+}
+
+// Rewrites the `autoload_roots` keys and `exclude` globs in the contents of
+// packwerk.yml line by line, so its comments and layout survive.
+fn rename_packwerk_yml_paths(contents: &str, old: &str, new: &str) -> String {
+    let mut section = "";
+    let mut lines = vec![];
+    for line in contents.split_inclusive('\n') {
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        let item = line.trim_start().strip_prefix('-');
+        if !indented && item.is_none() && !line.trim().is_empty() {
+            section = line.split(':').next().unwrap_or_default();
+        }
+        let (path, renamed_path) = match (section, item) {
+            ("autoload_roots", None) if indented => {
+                let key =
+                    line.trim_start().split(':').next().unwrap_or_default();
+                let key = key.trim_matches(|c| c == '"' || c == '\'');
+                (key, renamed(key, old, new))
+            }
+            ("exclude", Some(item)) => {
+                let glob = item.trim().trim_matches(|c| c == '"' || c == '\'');
+                (glob, renamed_glob(glob, old, new))
+            }
+            _ => ("", None),
+        };
+        match renamed_path {
+            Some(renamed_path) => {
+                lines.push(line.replacen(path, &renamed_path, 1))
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+    lines.concat()
+}
+
+// `glob` with the `old` pack path replaced by `new`, if it starts with it.
+// A leading `!` is kept.
+fn renamed_glob(glob: &str, old: &str, new: &str) -> Option<String> {
+    match glob.strip_prefix('!') {
+        Some(negated) => {
+            renamed(negated, old, new).map(|glob| format!("!{}", glob))
+        }
+        None => renamed(glob, old, new),
+    }
+}
+
+// Writing package_todo.yml files only needs the identifier
+fn recorded_violation(identifier: ViolationIdentifier) -> Violation {
+    Violation {
+        identifier,
+        source_location: SourceLocation::default(),
+        referencing_pack_relative_yml: String::new(),
+        defining_layer: None,
+        referencing_layer: None,
+    }
+}
+
+// Files that are not included (e.g. READMEs) are not indexed by the pack set,
+// so fall back to the innermost pack containing the path.
+fn owning_pack<'a>(
+    configuration: &'a Configuration,
+    absolute_path: &Path,
+) -> anyhow::Result<&'a Pack> {
+    if let Some(pack) = configuration.pack_set.for_file(absolute_path)? {
+        return Ok(pack);
+    }
+    configuration
+        .pack_set
+        .packs
+        .iter()
+        .filter(|pack| absolute_path.starts_with(pack_path(pack)))
+        .max_by_key(|pack| pack_path(pack).components().count())
+        .context(format!(
+            "`{}` does not belong to a pack",
+            absolute_path.display()
+        ))
+}
+
+fn pack_path(pack: &Pack) -> PathBuf {
+    pack.yml.parent().map(PathBuf::from).unwrap_or_default()
+}
+
+fn files_under(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(path)? {
+        files.extend(files_under(&entry?.path())?);
+    }
+    Ok(files)
+}
+
+fn remove_empty_dirs(path: &Path) -> anyhow::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            remove_empty_dirs(&entry.path())?;
+        }
+    }
+    if fs::read_dir(path)?.next().is_none() {
+        fs::remove_dir(path)?;
+    }
+    Ok(())
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renamed() {
+        assert_eq!(
+            renamed("packs/foo", "packs/foo", "packs/bar"),
+            Some("packs/bar".to_string())
+        );
+        assert_eq!(
+            renamed("packs/foo/app/services", "packs/foo", "packs/bar"),
+            Some("packs/bar/app/services".to_string())
+        );
+        assert_eq!(renamed("packs/foobar", "packs/foo", "packs/bar"), None);
+    }
+
+    #[test]
+    fn test_rename_packwerk_yml_paths() {
+        let contents = "# packs/foo/app is autoloaded\ncache: false\n\nautoload_roots:\n  packs/foo/app/models: \"::Foo\"\n  \"packs/foo/lib\": \"::Object\"\n  packs/foobar/app: \"::Foobar\"\n\nexclude:\n  - packs/foo/tmp\n- \"!packs/foo/tmp/keep/**\"\n  - packs/foobar/tmp\n  - \"{bin,tmp}/**/*\"\n";
+
+        assert_eq!(
+            rename_packwerk_yml_paths(contents, "packs/foo", "components/foo"),
+            "# packs/foo/app is autoloaded\ncache: false\n\nautoload_roots:\n  components/foo/app/models: \"::Foo\"\n  \"components/foo/lib\": \"::Object\"\n  packs/foobar/app: \"::Foobar\"\n\nexclude:\n  - components/foo/tmp\n- \"!components/foo/tmp/keep/**\"\n  - packs/foobar/tmp\n  - \"{bin,tmp}/**/*\"\n"
+        );
+    }
+
+    #[test]
+    fn test_renamed_glob() {
+        assert_eq!(
+            renamed_glob("packs/foo/**", "packs/foo", "packs/bar"),
+            Some("packs/bar/**".to_string())
+        );
+        assert_eq!(
+            renamed_glob("!packs/foo/tmp", "packs/foo", "packs/bar"),
+            Some("!packs/bar/tmp".to_string())
+        );
+        assert_eq!(
+            renamed_glob("**/packs/foo", "packs/foo", "packs/bar"),
+            None
+        );
+    }
+}
@@ -16,6 +16,7 @@ use super::{
     checker::ViolationIdentifier, file_utils::expand_glob, ignored, PackageTodo,
 };
 
+/// A pack, as configured by its package.yml.
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Pack {
     #[serde(default, skip_serializing_if = "Option::is_none")]
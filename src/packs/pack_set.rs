@@ -8,6 +8,7 @@ use itertools::Itertools;
 
 use super::{checker::ViolationIdentifier, pack::Pack, Configuration};
 
+/// All packs in an application, indexed by name and by the files they own.
 #[derive(Default, Debug)]
 pub struct PackSet {
     pub packs: Vec<Pack>,
@@ -76,6 +77,7 @@ impl PackSet {
         })
     }
 
+    /// The pack that owns the file at `absolute_file_path`, if any.
     pub fn for_file(
         &self,
         absolute_file_path: &Path,
@@ -92,6 +94,7 @@ impl PackSet {
             })
     }
 
+    /// The pack named `pack_name`, e.g. `packs/foo`.
     pub fn for_pack(&self, pack_name: &str) -> Result<&Pack> {
         // Trim trailing slash on pack_name.
         // Since often the input arg here comes from the command line,
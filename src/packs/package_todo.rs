@@ -135,25 +135,8 @@ pub fn write_violations_to_disk(
     violations: HashSet<Violation>,
 ) {
     debug!("Starting writing violations to disk");
-    // First we need to group the violations by the responsible pack, which today is always the referencing pack
-    // Later if we change where a violation shows up, we should delegate to the checker
-    // to decide what pack it should be in.
-    let mut violations_by_responsible_pack: HashMap<String, Vec<Violation>> =
-        HashMap::new();
-    for violation in violations {
-        if violation.identifier.strict {
-            continue;
-        }
-        let referencing_pack_name =
-            violation.identifier.referencing_pack_name.to_owned();
-        violations_by_responsible_pack
-            .entry(referencing_pack_name)
-            .or_default()
-            .push(violation);
-    }
-
     let package_todos_by_pack_name =
-        package_todos_for_pack_name(violations_by_responsible_pack);
+        package_todos_for_pack_name(violations_by_responsible_pack(violations));
 
     let all_packs = &configuration.pack_set.packs;
     all_packs.par_iter().for_each(|p| {
@@ -171,6 +154,49 @@ pub fn write_violations_to_disk(
     debug!("Finished writing violations to disk");
 }
 
+/// The contents of the package_todo.yml recording `violations` for each pack,
+/// by pack name. Packs missing from the result have no package_todo.yml.
+pub(crate) fn serialized_package_todos(
+    violations: HashSet<Violation>,
+    packs_first_mode: bool,
+) -> HashMap<String, String> {
+    package_todos_for_pack_name(violations_by_responsible_pack(violations))
+        .iter()
+        .map(|(pack_name, package_todo)| {
+            (
+                pack_name.clone(),
+                serialize_package_todo(
+                    pack_name,
+                    package_todo,
+                    packs_first_mode,
+                ),
+            )
+        })
+        .collect()
+}
+
+fn violations_by_responsible_pack(
+    violations: HashSet<Violation>,
+) -> HashMap<String, Vec<Violation>> {
+    // The responsible pack is always the referencing pack today
+    // Later if we change where a violation shows up, we should delegate to the checker
+    // to decide what pack it should be in.
+    let mut violations_by_responsible_pack: HashMap<String, Vec<Violation>> =
+        HashMap::new();
+    for violation in violations {
+        if violation.identifier.strict {
+            continue;
+        }
+        let referencing_pack_name =
+            violation.identifier.referencing_pack_name.to_owned();
+        violations_by_responsible_pack
+            .entry(referencing_pack_name)
+            .or_default()
+            .push(violation);
+    }
+    violations_by_responsible_pack
+}
+
 fn serialize_package_todo(
     responsible_pack_name: &String,
     package_todo: &PackageTodo,
@@ -1,2 +1,28 @@
+use line_col::LineColLookup;
+use regex::Regex;
+
+use super::ParseDiagnostic;
+
 pub(crate) mod experimental;
 pub(crate) mod packwerk;
+
+/// Diagnostics for the `# pks:` comments in ERB tags. Without source maps,
+/// their lines cannot be matched to the lines of references, so they are
+/// reported instead of being ignored.
+pub(crate) fn unsupported_suppressions(contents: &str) -> Vec<ParseDiagnostic> {
+    let regex = Regex::new(r"<%-?\s*#\s*pks:(disable|enable)").unwrap();
+    let lookup = LineColLookup::new(contents);
+    regex
+        .find_iter(contents)
+        .map(|comment| {
+            let (line, column) = lookup.get(comment.start());
+            ParseDiagnostic {
+                line,
+                column: column - 1,
+                message: String::from(
+                    "`# pks:` comments are not supported in ERB files",
+                ),
+            }
+        })
+        .collect()
+}
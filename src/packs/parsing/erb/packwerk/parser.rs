@@ -1,10 +1,12 @@
 use crate::packs::file_utils::file_read_contents;
 use crate::packs::{
     file_utils::convert_erb_to_ruby_without_sourcemaps, parsing::Range,
-    Configuration, ProcessedFile, UnresolvedReference,
+    Configuration, ParseDiagnostic, ProcessedFile, UnresolvedReference,
 };
 use std::path::Path;
 
+use crate::packs::parsing::erb::unsupported_suppressions;
+
 use crate::packs::parsing::ruby::packwerk::parser::process_from_contents as process_from_ruby_contents;
 
 pub(crate) fn process_from_path(
@@ -20,6 +22,7 @@ pub(crate) fn process_from_contents(
     path: &Path,
     configuration: &Configuration,
 ) -> ProcessedFile {
+    let invalid_suppressions = unsupported_suppressions(&contents);
     let ruby_contents = convert_erb_to_ruby_without_sourcemaps(contents);
     let processed_file =
         process_from_ruby_contents(ruby_contents, path, configuration);
@@ -35,10 +38,25 @@ pub(crate) fn process_from_contents(
             ..r.clone()
         })
         .collect();
+    // Without source maps, the locations would point into the generated Ruby,
+    // so parse errors are reported at the start of the file. Lines start at 1
+    // in every output format.
+    let parse_errors = processed_file
+        .parse_errors
+        .into_iter()
+        .map(|e| ParseDiagnostic {
+            line: 1,
+            column: 0,
+            ..e
+        })
+        .collect();
 
     ProcessedFile {
         absolute_path: path.to_path_buf(),
         unresolved_references: references_without_range,
         definitions: vec![],
+        parse_errors,
+        suppressions: vec![],
+        invalid_suppressions,
     }
 }
@@ -4,10 +4,14 @@ use std::{
 };
 
 pub(crate) mod ruby;
+pub(crate) use ruby::experimental::parser::process_from_contents as process_from_ruby_contents_experimental;
 pub(crate) use ruby::experimental::parser::process_from_path as process_from_ruby_path_experimental;
+pub(crate) use ruby::packwerk::parser::process_from_contents as process_from_ruby_contents;
 pub(crate) use ruby::packwerk::parser::process_from_path as process_from_ruby_path;
 mod erb;
+pub(crate) use erb::experimental::parser::process_from_contents as process_from_erb_contents_experimental;
 pub(crate) use erb::experimental::parser::process_from_path as process_from_erb_path_experimental;
+pub(crate) use erb::packwerk::parser::process_from_contents as process_from_erb_contents;
 pub(crate) use erb::packwerk::parser::process_from_path as process_from_erb_path;
 
 use crate::packs::file_utils::is_stdin_file;
@@ -17,7 +21,7 @@ use serde::{Deserialize, Serialize};
 use super::{
     caching::{cache::Cache, CacheResult},
     file_utils::{get_file_type, SupportedFileType},
-    Configuration, ProcessedFile,
+    CheckerType, Configuration, ProcessedFile,
 };
 
 pub fn process_file(
@@ -53,6 +57,9 @@ pub fn process_file(
             absolute_path: path.to_path_buf(),
             unresolved_references: vec![],
             definitions: vec![], // TODO
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         })
     };
 
@@ -63,6 +70,47 @@ pub fn process_file(
     result
 }
 
+/// Like `process_file`, but parses `contents` instead of reading `path` from
+/// disk. Used for buffers that have not been saved yet, e.g. by the language server.
+pub(crate) fn process_file_contents(
+    path: &Path,
+    contents: String,
+    configuration: &Configuration,
+) -> ProcessedFile {
+    match get_file_type(path) {
+        Some(SupportedFileType::Ruby) => {
+            if configuration.experimental_parser {
+                process_from_ruby_contents_experimental(
+                    contents,
+                    path,
+                    configuration,
+                )
+            } else {
+                process_from_ruby_contents(contents, path, configuration)
+            }
+        }
+        Some(SupportedFileType::Erb) => {
+            if configuration.experimental_parser {
+                process_from_erb_contents_experimental(
+                    contents,
+                    path,
+                    configuration,
+                )
+            } else {
+                process_from_erb_contents(contents, path, configuration)
+            }
+        }
+        None => ProcessedFile {
+            absolute_path: path.to_path_buf(),
+            unresolved_references: vec![],
+            definitions: vec![],
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
+        },
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct UnresolvedReference {
     pub name: String,
@@ -78,6 +126,31 @@ pub struct Range {
     pub end_col: usize,
 }
 
+/// A syntax error reported by the parser. References after it may be missing
+/// from the file's `ProcessedFile`, or the file may have none at all.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Hash)]
+pub struct ParseDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// A `# pks:disable` comment, suppressing violations of `violation_types` on
+/// the lines from `start_line` to `end_line`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Hash)]
+pub struct Suppression {
+    pub violation_types: Vec<CheckerType>,
+    pub reason: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl Suppression {
+    pub fn covers(&self, line: usize) -> bool {
+        self.start_line <= line && line <= self.end_line
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
 pub struct ParsedDefinition {
     pub fully_qualified_name: String,
@@ -79,6 +79,9 @@ mod tests {
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -108,6 +111,9 @@ mod tests {
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -138,6 +144,9 @@ mod tests {
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -168,6 +177,9 @@ mod tests {
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -194,6 +206,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -230,6 +245,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -266,6 +284,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -301,6 +322,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -340,6 +364,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -380,6 +407,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
 
         assert_eq!(expected, actual);
@@ -421,6 +451,9 @@ end
             absolute_path,
             unresolved_references,
             definitions,
+            parse_errors: vec![],
+            suppressions: vec![],
+            invalid_suppressions: vec![],
         };
 
         assert_eq!(expected, actual);
@@ -1,10 +1,14 @@
 use crate::packs::file_utils::file_read_contents;
 use crate::packs::{
     parsing::{
-        ruby::parse_utils::{
-            fetch_const_const_name, fetch_const_name, fetch_node_location,
-            get_constant_assignment_definition, get_definition_from,
-            get_reference_from_active_record_association, loc_to_range,
+        ruby::{
+            parse_utils::{
+                fetch_const_const_name, fetch_const_name, fetch_node_location,
+                get_constant_assignment_definition, get_definition_from,
+                get_reference_from_active_record_association, loc_to_range,
+                parse_errors,
+            },
+            suppressions::suppressions,
         },
         ParsedDefinition, UnresolvedReference,
     },
@@ -201,6 +205,9 @@ pub(crate) fn process_from_contents(
     let parser = Parser::new(contents.clone(), options);
     let parse_result = parser.do_parse();
 
+    let parse_errors = parse_errors(&parse_result.diagnostics, &lookup);
+    let (suppressions, invalid_suppressions) =
+        suppressions(&contents, &parse_result.comments, &lookup);
     let ast_option: Option<Box<Node>> = parse_result.ast;
 
     let ast = match ast_option {
@@ -210,6 +217,9 @@ pub(crate) fn process_from_contents(
                 absolute_path: path.to_owned(),
                 unresolved_references: vec![],
                 definitions: vec![],
+                parse_errors,
+                suppressions,
+                invalid_suppressions,
             }
         }
     };
@@ -238,5 +248,8 @@ pub(crate) fn process_from_contents(
         absolute_path,
         unresolved_references,
         definitions,
+        parse_errors,
+        suppressions,
+        invalid_suppressions,
     }
 }
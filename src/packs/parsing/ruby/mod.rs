@@ -5,4 +5,5 @@ pub(crate) mod packwerk;
 mod parse_utils;
 mod rails_utils;
 mod ruby_utils;
+mod suppressions;
 pub(crate) mod zeitwerk;
@@ -5,7 +5,7 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::packs::parsing::ruby::packwerk::parser::process_from_contents;
-    use crate::packs::parsing::Range;
+    use crate::packs::parsing::{ParseDiagnostic, Range};
     use crate::packs::{Configuration, UnresolvedReference};
 
     #[test]
@@ -1310,4 +1310,24 @@ end
             *reference,
         );
     }
+
+    #[test]
+    fn syntax_error() {
+        let contents: String = String::from("Foo\ndef broken(\nend\n");
+        let configuration = Configuration::default();
+        let processed_file = process_from_contents(
+            contents,
+            &PathBuf::from("path/to/file.rb"),
+            &configuration,
+        );
+        assert_eq!(
+            vec![ParseDiagnostic {
+                line: 3,
+                column: 0,
+                message: String::from("unexpected kEND"),
+            }],
+            processed_file.parse_errors
+        );
+        assert!(processed_file.unresolved_references.is_empty());
+    }
 }
@@ -7,7 +7,9 @@ use crate::packs::{
                 fetch_const_const_name, fetch_const_name, fetch_node_location,
                 get_constant_assignment_definition, get_definition_from,
                 get_reference_from_active_record_association, loc_to_range,
+                parse_errors,
             },
+            suppressions::suppressions,
         },
         ParsedDefinition, Range, UnresolvedReference,
     },
@@ -222,6 +224,9 @@ pub(crate) fn process_from_contents(
     let parser = Parser::new(contents.clone(), options);
     let parse_result = parser.do_parse();
 
+    let parse_errors = parse_errors(&parse_result.diagnostics, &lookup);
+    let (suppressions, invalid_suppressions) =
+        suppressions(&contents, &parse_result.comments, &lookup);
     let ast_option: Option<Box<Node>> = parse_result.ast;
 
     let ast = match ast_option {
@@ -231,6 +236,9 @@ pub(crate) fn process_from_contents(
                 absolute_path: path.to_owned(),
                 unresolved_references: vec![],
                 definitions: vec![],
+                parse_errors,
+                suppressions,
+                invalid_suppressions,
             }
         }
     };
@@ -311,5 +319,8 @@ pub(crate) fn process_from_contents(
         absolute_path,
         unresolved_references,
         definitions,
+        parse_errors,
+        suppressions,
+        invalid_suppressions,
     }
 }
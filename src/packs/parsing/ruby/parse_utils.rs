@@ -1,9 +1,11 @@
 use std::collections::HashSet;
 
-use lib_ruby_parser::{nodes, Loc, Node};
+use lib_ruby_parser::{nodes, Diagnostic, Loc, Node};
 use line_col::LineColLookup;
 
-use crate::packs::parsing::{ParsedDefinition, Range, UnresolvedReference};
+use crate::packs::parsing::{
+    ParseDiagnostic, ParsedDefinition, Range, UnresolvedReference,
+};
 
 use super::inflector_shim::to_class_case;
 
@@ -60,6 +62,25 @@ pub fn loc_to_range(loc: &Loc, lookup: &LineColLookup) -> Range {
     }
 }
 
+/// The syntax errors among the parser's diagnostics, skipping warnings.
+pub fn parse_errors(
+    diagnostics: &[Diagnostic],
+    lookup: &LineColLookup,
+) -> Vec<ParseDiagnostic> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .map(|diagnostic| {
+            let range = loc_to_range(&diagnostic.loc, lookup);
+            ParseDiagnostic {
+                line: range.start_row,
+                column: range.start_col,
+                message: diagnostic.render_message(),
+            }
+        })
+        .collect()
+}
+
 pub fn fetch_const_name(node: &nodes::Node) -> Result<String, ParseError> {
     match node {
         Node::Const(const_node) => Ok(fetch_const_const_name(const_node)?),
@@ -0,0 +1,222 @@
+//! `# pks:disable` comments, which suppress violations of individual
+//! references:
+//!
+//! ```ruby
+//! # pks:disable privacy,dependency -- Billing owns the refund flow
+//! Payments::Ledger.refund(order)
+//!
+//! Payments::Ledger.balance # pks:disable privacy -- Read-only
+//!
+//! # pks:disable-block dependency -- Removed with the v1 API
+//! Payments::Legacy.charge(order)
+//! Payments::Legacy.capture(order)
+//! # pks:enable-block
+//! ```
+//!
+//! A comment on a line of its own applies to the next line, a comment after
+//! code applies to its own line, and a block applies up to the matching
+//! `# pks:enable-block` (or the end of the file). Every comment needs a
+//! reason after `--`, and comments that cannot be read fail `pks check`.
+//! Violations of checkers enforced in strict mode are never suppressed.
+
+use std::str::FromStr;
+
+use lib_ruby_parser::source::{Comment, CommentType};
+use line_col::LineColLookup;
+
+use crate::packs::parsing::{ParseDiagnostic, Suppression};
+use crate::packs::CheckerType;
+
+const DISABLE: &str = "pks:disable";
+const DISABLE_BLOCK: &str = "pks:disable-block";
+const ENABLE_BLOCK: &str = "pks:enable-block";
+
+/// The suppressions in `comments`, along with diagnostics for the `pks:`
+/// comments that could not be read.
+pub(crate) fn suppressions(
+    contents: &str,
+    comments: &[Comment],
+    lookup: &LineColLookup,
+) -> (Vec<Suppression>, Vec<ParseDiagnostic>) {
+    let last_line = contents.lines().count().max(1);
+    let mut suppressions = vec![];
+    let mut diagnostics = vec![];
+    // Innermost last
+    let mut open_blocks: Vec<Suppression> = vec![];
+
+    for comment in comments {
+        if comment.kind != CommentType::Inline {
+            continue;
+        }
+        let location = &comment.location;
+        let Some(text) = contents.get(location.begin..location.end) else {
+            continue;
+        };
+        let text = text.trim_start_matches('#').trim();
+        if !text.starts_with(DISABLE) && !text.starts_with("pks:enable") {
+            continue;
+        }
+        let (directive, arguments) =
+            text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let (line, column) = lookup.get(location.begin);
+        let diagnostic = |message: String| ParseDiagnostic {
+            line,
+            column: column - 1,
+            message,
+        };
+
+        match directive {
+            DISABLE | DISABLE_BLOCK => {
+                let (violation_types, reason) = match parse_arguments(arguments)
+                {
+                    Ok(parsed) => parsed,
+                    Err(error) => {
+                        diagnostics.push(diagnostic(format!(
+                            "Invalid `# {}` comment: {}",
+                            directive, error
+                        )));
+                        continue;
+                    }
+                };
+                if directive == DISABLE_BLOCK {
+                    open_blocks.push(Suppression {
+                        violation_types,
+                        reason,
+                        start_line: line,
+                        end_line: last_line,
+                    });
+                } else {
+                    let is_own_line = contents[..location.begin]
+                        .rsplit('\n')
+                        .next()
+                        .is_some_and(|before| before.trim().is_empty());
+                    let suppressed_line =
+                        if is_own_line { line + 1 } else { line };
+                    suppressions.push(Suppression {
+                        violation_types,
+                        reason,
+                        start_line: suppressed_line,
+                        end_line: suppressed_line,
+                    });
+                }
+            }
+            ENABLE_BLOCK => match open_blocks.pop() {
+                Some(block) => suppressions.push(Suppression {
+                    end_line: line,
+                    ..block
+                }),
+                None => diagnostics.push(diagnostic(format!(
+                    "`# {}` without a matching `# {}`",
+                    ENABLE_BLOCK, DISABLE_BLOCK
+                ))),
+            },
+            _ => diagnostics.push(diagnostic(format!(
+                "Unknown comment `# {}`, expected `# {}`, `# {}` or `# {}`",
+                directive, DISABLE, DISABLE_BLOCK, ENABLE_BLOCK
+            ))),
+        }
+    }
+
+    // Unclosed blocks run to the end of the file
+    suppressions.extend(open_blocks);
+    (suppressions, diagnostics)
+}
+
+// "privacy,dependency -- reason"
+fn parse_arguments(
+    arguments: &str,
+) -> Result<(Vec<CheckerType>, String), String> {
+    let usage = "e.g. `# pks:disable privacy -- <reason>`";
+    let Some((violation_types, reason)) = arguments.split_once("--") else {
+        return Err(format!("a reason is required, {}", usage));
+    };
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(format!("a reason is required, {}", usage));
+    }
+    let violation_types = violation_types
+        .split(',')
+        .map(str::trim)
+        .filter(|violation_type| !violation_type.is_empty())
+        .map(CheckerType::from_str)
+        .collect::<Result<Vec<CheckerType>, String>>()?;
+    if violation_types.is_empty() {
+        return Err(format!("the violation types are required, {}", usage));
+    }
+    Ok((violation_types, reason.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use lib_ruby_parser::{Parser, ParserOptions};
+
+    use super::*;
+
+    fn parse(contents: &str) -> (Vec<Suppression>, Vec<ParseDiagnostic>) {
+        let lookup = LineColLookup::new(contents);
+        let result = Parser::new(contents, ParserOptions::default()).do_parse();
+        suppressions(contents, &result.comments, &lookup)
+    }
+
+    #[test]
+    fn test_line_suppressions() {
+        let (suppressions, diagnostics) = parse(
+            "# pks:disable privacy, dependency -- Allowed\nFoo\nBar # pks:disable layer -- Also allowed\n",
+        );
+        assert_eq!(diagnostics, vec![]);
+        assert_eq!(
+            suppressions,
+            vec![
+                Suppression {
+                    violation_types: vec![
+                        CheckerType::Privacy,
+                        CheckerType::Dependency
+                    ],
+                    reason: String::from("Allowed"),
+                    start_line: 2,
+                    end_line: 2,
+                },
+                Suppression {
+                    violation_types: vec![CheckerType::Layer],
+                    reason: String::from("Also allowed"),
+                    start_line: 3,
+                    end_line: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_suppressions() {
+        let (suppressions, diagnostics) = parse(
+            "# pks:disable-block privacy -- Legacy\nFoo\nBar\n# pks:enable-block\nBaz\n  # pks:disable-block dependency -- Rest of the file\nQux\n",
+        );
+        assert_eq!(diagnostics, vec![]);
+        let lines: Vec<(usize, usize)> = suppressions
+            .iter()
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        assert_eq!(lines, vec![(1, 4), (6, 7)]);
+    }
+
+    #[test]
+    fn test_invalid_comments() {
+        let (suppressions, diagnostics) = parse(
+            "# pks:disable privacy\nFoo\n# pks:disable secrecy -- Why\nBar\n# pks:disabled privacy -- Typo\n# pks:enable-block\n# pks: not a directive\n",
+        );
+        assert_eq!(suppressions, vec![]);
+        let messages: Vec<(usize, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (1, "Invalid `# pks:disable` comment: a reason is required, e.g. `# pks:disable privacy -- <reason>`"),
+                (3, "Invalid `# pks:disable` comment: Unknown checker type: secrecy"),
+                (5, "Unknown comment `# pks:disabled`, expected `# pks:disable`, `# pks:disable-block` or `# pks:enable-block`"),
+                (6, "`# pks:enable-block` without a matching `# pks:disable-block`"),
+            ]
+        );
+    }
+}
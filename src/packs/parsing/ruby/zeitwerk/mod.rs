@@ -124,9 +124,11 @@ fn inferred_constants_from_autoload_paths(
         .into_iter()
         .par_bridge()
         .map(|(absolute_path_of_definition, absolute_autoload_path)| {
-            if let Some(fully_qualified_name) = cache_data
-                .file_definition_map
-                .get(absolute_path_of_definition)
+            let relative_path = absolute_path_of_definition
+                .strip_prefix(configuration.absolute_root)
+                .unwrap_or(absolute_path_of_definition);
+            if let Some(fully_qualified_name) =
+                cache_data.file_definition_map.get(relative_path)
             {
                 ConstantDefinition {
                     fully_qualified_name: fully_qualified_name.to_owned(),
@@ -149,6 +151,7 @@ fn inferred_constants_from_autoload_paths(
     debug!("Caching constant definitions");
     cache_constant_definitions(
         &constants,
+        configuration.absolute_root,
         configuration.cache_directory,
         !configuration.cache_enabled,
     );
@@ -178,6 +181,7 @@ fn inferred_constant_from_file(
     }
 }
 
+// Keyed by path relative to the root, so the cache can move between checkouts
 #[derive(Serialize, Deserialize)]
 struct ConstantResolverCache {
     file_definition_map: HashMap<PathBuf, String>,
@@ -200,6 +204,7 @@ fn get_constant_resolver_cache(cache_dir: &Path) -> ConstantResolverCache {
 
 fn cache_constant_definitions(
     constants: &Vec<ConstantDefinition>,
+    absolute_root: &Path,
     cache_dir: &Path,
     cache_disabled: bool,
 ) {
@@ -209,8 +214,11 @@ fn cache_constant_definitions(
 
     let mut file_definition_map: HashMap<PathBuf, String> = HashMap::new();
     for constant in constants {
+        let path = &constant.absolute_path_of_definition;
         file_definition_map.insert(
-            constant.absolute_path_of_definition.clone(),
+            path.strip_prefix(absolute_root)
+                .unwrap_or(path)
+                .to_path_buf(),
             constant.fully_qualified_name.clone(),
         );
     }
@@ -0,0 +1,74 @@
+//! Unused public API detection for `pks list-unused-public-api`.
+//!
+//! A constant in a pack's public folder is only worth exposing if another
+//! pack uses it. References from within the defining pack do not count, since
+//! they would work just as well if the constant were private.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use super::reference_extractor::extract_references;
+use super::Configuration;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnusedConstant {
+    pub pack_name: String,
+    pub constant_name: String,
+    /// Relative to the root
+    pub relative_path: PathBuf,
+}
+
+/// Constants defined in each pack's public folder that no other pack
+/// references, sorted by pack and constant name.
+pub fn unused_public_constants(
+    configuration: &Configuration,
+) -> anyhow::Result<Vec<UnusedConstant>> {
+    let extracted =
+        extract_references(configuration, &configuration.included_files)?;
+
+    // Constants with the same name in different packs are different
+    // constants, so usage is by (defining pack, constant)
+    let used_by_other_packs: HashSet<(&str, &str)> = extracted
+        .references
+        .iter()
+        .filter_map(|reference| {
+            let defining_pack_name = reference.defining_pack_name.as_ref()?;
+            (defining_pack_name != &reference.referencing_pack_name).then_some(
+                (
+                    defining_pack_name.as_str(),
+                    reference.constant_name.as_str(),
+                ),
+            )
+        })
+        .collect();
+
+    let mut unused = vec![];
+    for (constant_name, definitions) in extracted
+        .constant_resolver
+        .fully_qualified_constant_name_to_constant_definition_map()
+    {
+        for definition in definitions {
+            let absolute_path = &definition.absolute_path_of_definition;
+            let Some(pack) = configuration.pack_set.for_file(absolute_path)?
+            else {
+                continue;
+            };
+            if used_by_other_packs
+                .contains(&(pack.name.as_str(), constant_name.as_str()))
+            {
+                continue;
+            }
+            let relative_path =
+                absolute_path.strip_prefix(&configuration.absolute_root)?;
+            if relative_path.starts_with(pack.public_folder()) {
+                unused.push(UnusedConstant {
+                    pack_name: pack.name.clone(),
+                    constant_name: constant_name.clone(),
+                    relative_path: relative_path.to_path_buf(),
+                });
+            }
+        }
+    }
+    unused.sort();
+    Ok(unused)
+}
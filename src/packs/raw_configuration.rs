@@ -79,6 +79,10 @@ pub(crate) struct RawConfiguration {
     #[serde(default)]
     pub checker_overrides: Option<CheckerOverrides>,
 
+    // Fail `check` when a file cannot be parsed
+    #[serde(default)]
+    pub fail_on_parse_errors: bool,
+
     // Whether to automatically respect .gitignore files
     #[serde(default = "default_respect_gitignore")]
     pub respect_gitignore: bool,
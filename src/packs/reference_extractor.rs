@@ -4,16 +4,41 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use tracing::debug;
 
 use crate::packs::{
-    get_experimental_constant_resolver, get_zeitwerk_constant_resolver,
-    process_files_with_cache, ProcessedFile,
+    caching::resolved_references::ResolvedReferencesCache,
+    constant_resolver::ConstantResolver, get_experimental_constant_resolver,
+    get_zeitwerk_constant_resolver, process_files_with_cache, ProcessedFile,
 };
 
-use super::{checker::reference::Reference, Configuration};
+use super::{
+    checker::{
+        invalid_suppressions_for, parse_errors_for, reference::Reference,
+        ParseError,
+    },
+    Configuration,
+};
 
 pub(crate) fn get_all_references(
     configuration: &Configuration,
     absolute_paths: &HashSet<PathBuf>,
 ) -> anyhow::Result<Vec<Reference>> {
+    Ok(extract_references(configuration, absolute_paths)?.references)
+}
+
+pub(crate) struct ExtractedReferences {
+    pub references: Vec<Reference>,
+    pub parse_errors: Vec<ParseError>,
+    pub invalid_suppressions: Vec<ParseError>,
+    /// The resolver the references were resolved with
+    pub constant_resolver: Box<dyn ConstantResolver + Send + Sync>,
+}
+
+/// The references in the files at `absolute_paths`, along with their syntax
+/// errors, invalid `# pks:` comments and the constant resolver used to
+/// resolve them.
+pub(crate) fn extract_references(
+    configuration: &Configuration,
+    absolute_paths: &HashSet<PathBuf>,
+) -> anyhow::Result<ExtractedReferences> {
     let cache = configuration.get_cache();
 
     debug!("Getting unresolved references (using cache if possible)");
@@ -55,23 +80,74 @@ pub(crate) fn get_all_references(
         (constant_resolver, processed_files)
     };
 
+    let references = if configuration.cache_enabled {
+        resolve_references_with_cache(
+            configuration,
+            constant_resolver.as_ref(),
+            &processed_files_to_check,
+        )?
+    } else {
+        resolve_references(
+            configuration,
+            constant_resolver.as_ref(),
+            &processed_files_to_check,
+        )?
+    };
+    let parse_errors =
+        parse_errors_for(configuration, &processed_files_to_check);
+    let invalid_suppressions =
+        invalid_suppressions_for(configuration, &processed_files_to_check);
+    Ok(ExtractedReferences {
+        references,
+        parse_errors,
+        invalid_suppressions,
+        constant_resolver,
+    })
+}
+
+/// Builds the constant resolver `get_all_references` would use, for callers
+/// that keep it around between checks (e.g. the language server).
+pub(crate) fn get_constant_resolver(
+    configuration: &Configuration,
+) -> anyhow::Result<Box<dyn ConstantResolver + Send + Sync>> {
+    if configuration.experimental_parser {
+        let all_processed_files: Vec<ProcessedFile> = process_files_with_cache(
+            &configuration.included_files,
+            configuration.get_cache(),
+            configuration,
+        )?;
+
+        Ok(get_experimental_constant_resolver(
+            &configuration.absolute_root,
+            &all_processed_files,
+            &configuration.ignored_definitions,
+        ))
+    } else {
+        Ok(get_zeitwerk_constant_resolver(
+            &configuration.pack_set,
+            &configuration.constant_resolver_configuration(),
+        ))
+    }
+}
+
+pub(crate) fn resolve_references(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_files: &[ProcessedFile],
+) -> anyhow::Result<Vec<Reference>> {
     debug!("Turning unresolved references into fully qualified references");
-    let references: anyhow::Result<Vec<Reference>> = processed_files_to_check
+    let references: anyhow::Result<Vec<Reference>> = processed_files
         .par_iter()
         .try_fold(
             Vec::new,
             // Start with an empty vector for each thread
             |mut acc, processed_file| {
                 // Try to fold results within a thread
-                for unresolved_ref in &processed_file.unresolved_references {
-                    let mut refs = Reference::from_unresolved_reference(
-                        configuration,
-                        constant_resolver.as_ref(),
-                        unresolved_ref,
-                        &processed_file.absolute_path,
-                    )?;
-                    acc.append(&mut refs); // Collect references, return error if any
-                }
+                acc.append(&mut resolve_file_references(
+                    configuration,
+                    constant_resolver,
+                    processed_file,
+                )?); // Collect references, return error if any
                 Ok(acc)
             },
         )
@@ -87,3 +163,60 @@ pub(crate) fn get_all_references(
 
     references
 }
+
+/// Like `resolve_references`, but reuses the references persisted by a
+/// previous run for files whose references and definitions are unchanged.
+fn resolve_references_with_cache(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_files: &[ProcessedFile],
+) -> anyhow::Result<Vec<Reference>> {
+    debug!("Resolving references (using cache if possible)");
+    let cache = ResolvedReferencesCache::new(configuration, constant_resolver);
+    let references = processed_files
+        .par_iter()
+        .map(|processed_file| -> anyhow::Result<Vec<Reference>> {
+            match cache.get(processed_file)? {
+                Some(references) => Ok(references),
+                None => cache.write(
+                    processed_file,
+                    resolve_file_references(
+                        configuration,
+                        constant_resolver,
+                        processed_file,
+                    )?,
+                ),
+            }
+        })
+        .collect::<anyhow::Result<Vec<Vec<Reference>>>>()?;
+    debug!("Finished resolving references");
+
+    Ok(references.into_iter().flatten().collect())
+}
+
+fn resolve_file_references(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_file: &ProcessedFile,
+) -> anyhow::Result<Vec<Reference>> {
+    let mut references = vec![];
+    for unresolved_ref in &processed_file.unresolved_references {
+        references.append(&mut Reference::from_unresolved_reference(
+            configuration,
+            constant_resolver,
+            unresolved_ref,
+            &processed_file.absolute_path,
+        )?);
+    }
+    if !processed_file.suppressions.is_empty() {
+        for reference in &mut references {
+            reference.suppressions = processed_file
+                .suppressions
+                .iter()
+                .filter(|s| s.covers(reference.source_location.line))
+                .cloned()
+                .collect();
+        }
+    }
+    Ok(references)
+}
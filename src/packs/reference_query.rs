@@ -0,0 +1,371 @@
+//! Querying resolved references for `pks references` and `pks why`.
+//!
+//! Unlike `check`, which only reports violations, this lists every reference
+//! matching the filters, e.g. everything that uses a pack's constants, and
+//! marks the ones that are violations.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::checker::{violations_for_references, ViolationIdentifier};
+use super::checker_configuration::CheckerType;
+use super::cli::ReferencesFormat;
+use super::reference_extractor::get_all_references;
+use super::Configuration;
+
+/// Which references to list. Unset filters match every reference.
+#[derive(Debug, Default)]
+pub struct ReferenceFilter {
+    pub from_pack: Option<String>,
+    pub to_pack: Option<String>,
+    pub constant: Option<String>,
+    /// Files or directories, relative to the root
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReferenceViolation {
+    pub violation_type: String,
+    pub strict: bool,
+    pub recorded: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct QueriedReference {
+    pub constant_name: String,
+    pub referencing_pack_name: String,
+    pub defining_pack_name: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub defining_file: Option<String>,
+    pub violations: Vec<ReferenceViolation>,
+}
+
+/// The resolved references matching `filter`, ordered by location.
+/// References to constants no pack defines are left out.
+pub fn query_references(
+    configuration: &Configuration,
+    filter: &ReferenceFilter,
+) -> anyhow::Result<Vec<QueriedReference>> {
+    let pack_set = &configuration.pack_set;
+    let from_pack = filter
+        .from_pack
+        .as_deref()
+        .map(|name| pack_set.for_pack(name))
+        .transpose()?;
+    let to_pack = filter
+        .to_pack
+        .as_deref()
+        .map(|name| pack_set.for_pack(name))
+        .transpose()?;
+    let constant = filter
+        .constant
+        .as_ref()
+        .map(|name| format!("::{}", name.trim_start_matches("::")));
+
+    let absolute_paths = configuration.intersect_files(filter.files.clone());
+    let references: Vec<_> =
+        get_all_references(configuration, &absolute_paths)?
+            .into_iter()
+            .filter(|reference| {
+                from_pack.is_none_or(|pack| {
+                    reference.referencing_pack_name == pack.name
+                }) && to_pack.is_none_or(|pack| {
+                    reference.defining_pack_name.as_ref() == Some(&pack.name)
+                }) && constant
+                    .as_ref()
+                    .is_none_or(|name| &reference.constant_name == name)
+                    && reference.defining_pack_name.is_some()
+            })
+            .collect();
+
+    // Violations are identified by file and constant, so every reference to
+    // a constant in a file shares its violations
+    let mut violations_by_file_and_constant: HashMap<
+        (String, String),
+        Vec<ReferenceViolation>,
+    > = HashMap::new();
+    for violation in violations_for_references(configuration, &references)? {
+        let identifier = &violation.identifier;
+        violations_by_file_and_constant
+            .entry((identifier.file.clone(), identifier.constant_name.clone()))
+            .or_default()
+            .push(ReferenceViolation {
+                violation_type: identifier.violation_type.to_string(),
+                strict: identifier.strict,
+                recorded: is_recorded(identifier, &pack_set.all_violations),
+            });
+    }
+    // One violation is found per reference, so a constant referenced more
+    // than once in a file has each violation several times
+    for violations in violations_by_file_and_constant.values_mut() {
+        violations.sort_by(|a, b| {
+            (&a.violation_type, a.strict).cmp(&(&b.violation_type, b.strict))
+        });
+        violations.dedup();
+    }
+
+    let mut queried: Vec<QueriedReference> = references
+        .into_iter()
+        .map(|reference| QueriedReference {
+            violations: violations_by_file_and_constant
+                .get(&(
+                    reference.relative_referencing_file.clone(),
+                    reference.constant_name.clone(),
+                ))
+                .cloned()
+                .unwrap_or_default(),
+            constant_name: reference.constant_name,
+            referencing_pack_name: reference.referencing_pack_name,
+            defining_pack_name: reference
+                .defining_pack_name
+                .unwrap_or_default(),
+            file: reference.relative_referencing_file,
+            line: reference.source_location.line,
+            column: reference.source_location.column,
+            defining_file: reference.relative_defining_file,
+        })
+        .collect();
+    queried.sort_by(|a, b| {
+        (&a.file, a.line, a.column, &a.constant_name).cmp(&(
+            &b.file,
+            b.line,
+            b.column,
+            &b.constant_name,
+        ))
+    });
+    Ok(queried)
+}
+
+// Recorded violations do not know whether they are strict, so that is not
+// compared
+fn is_recorded(
+    identifier: &ViolationIdentifier,
+    recorded_violations: &HashSet<ViolationIdentifier>,
+) -> bool {
+    recorded_violations.contains(&ViolationIdentifier {
+        strict: false,
+        ..identifier.clone()
+    })
+}
+
+/// How a dependency from one pack to another is accounted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStatus {
+    /// Listed in the referencing pack's `dependencies`
+    Declared,
+    /// Not declared, but recorded in its package_todo.yml
+    Recorded,
+    Undeclared,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ConstantReferences {
+    pub constant_name: String,
+    pub defining_file: Option<String>,
+    pub references: Vec<QueriedReference>,
+}
+
+/// The references that make `from_pack` depend on `to_pack`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    pub from_pack: String,
+    pub to_pack: String,
+    pub status: EdgeStatus,
+    /// Sorted by constant name
+    pub constants: Vec<ConstantReferences>,
+}
+
+pub fn explain_dependency(
+    configuration: &Configuration,
+    from_pack: &str,
+    to_pack: &str,
+) -> anyhow::Result<Explanation> {
+    let from = configuration.pack_set.for_pack(from_pack)?;
+    let to = configuration.pack_set.for_pack(to_pack)?;
+    let status = if from.dependencies.contains(&to.name) {
+        EdgeStatus::Declared
+    } else if from
+        .package_todo
+        .violations_by_defining_pack
+        .get(&to.name)
+        .is_some_and(|groups| {
+            // Other violations on `to` do not record a dependency
+            groups.values().any(|group| {
+                group
+                    .violation_types
+                    .contains(&CheckerType::Dependency.to_string())
+            })
+        })
+    {
+        EdgeStatus::Recorded
+    } else {
+        EdgeStatus::Undeclared
+    };
+
+    let filter = ReferenceFilter {
+        from_pack: Some(from.name.clone()),
+        to_pack: Some(to.name.clone()),
+        ..ReferenceFilter::default()
+    };
+    let mut by_constant: BTreeMap<String, ConstantReferences> = BTreeMap::new();
+    for reference in query_references(configuration, &filter)? {
+        by_constant
+            .entry(reference.constant_name.clone())
+            .or_insert_with(|| ConstantReferences {
+                constant_name: reference.constant_name.clone(),
+                defining_file: reference.defining_file.clone(),
+                references: vec![],
+            })
+            .references
+            .push(reference);
+    }
+
+    Ok(Explanation {
+        from_pack: from.name.clone(),
+        to_pack: to.name.clone(),
+        status,
+        constants: by_constant.into_values().collect(),
+    })
+}
+
+impl Explanation {
+    pub fn render(&self, format: ReferencesFormat) -> anyhow::Result<String> {
+        match format {
+            ReferencesFormat::Text => Ok(self.to_text()),
+            ReferencesFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let status = match self.status {
+            EdgeStatus::Declared => {
+                "is declared in its package.yml `dependencies`"
+            }
+            EdgeStatus::Recorded => {
+                "is not declared, but is recorded in its package_todo.yml"
+            }
+            EdgeStatus::Undeclared => {
+                "is not declared in its package.yml `dependencies`"
+            }
+        };
+        let _ = writeln!(
+            out,
+            "The dependency of {} on {} {}.",
+            self.from_pack, self.to_pack, status
+        );
+
+        if self.constants.is_empty() {
+            let _ = writeln!(
+                out,
+                "\nNo references from {} to {}.",
+                self.from_pack, self.to_pack
+            );
+            if self.status == EdgeStatus::Declared {
+                let _ = writeln!(out, "The dependency can be removed.");
+            }
+            return out;
+        }
+
+        let reference_count: usize = self
+            .constants
+            .iter()
+            .map(|constant| constant.references.len())
+            .sum();
+        let _ = writeln!(
+            out,
+            "\n{} reference(s) to {} constant(s):",
+            reference_count,
+            self.constants.len()
+        );
+        for constant in &self.constants {
+            let _ = write!(out, "\n{}", constant.constant_name);
+            if let Some(defining_file) = &constant.defining_file {
+                let _ = write!(out, " ({})", defining_file);
+            }
+            let _ = writeln!(out);
+            for reference in &constant.references {
+                let _ = writeln!(
+                    out,
+                    "  {}:{}:{}{}",
+                    reference.file,
+                    reference.line,
+                    reference.column,
+                    violation_summary(&reference.violations)
+                );
+            }
+        }
+        out
+    }
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    references: &'a [QueriedReference],
+}
+
+pub fn render(
+    references: &[QueriedReference],
+    format: ReferencesFormat,
+) -> anyhow::Result<String> {
+    match format {
+        ReferencesFormat::Text => Ok(to_text(references)),
+        ReferencesFormat::Json => {
+            Ok(serde_json::to_string_pretty(&JsonOutput { references })?)
+        }
+    }
+}
+
+fn to_text(references: &[QueriedReference]) -> String {
+    let mut out = String::new();
+    for reference in references {
+        let _ = writeln!(
+            out,
+            "{}:{}:{} {} ({} -> {}){}",
+            reference.file,
+            reference.line,
+            reference.column,
+            reference.constant_name,
+            reference.referencing_pack_name,
+            reference.defining_pack_name,
+            violation_summary(&reference.violations)
+        );
+    }
+    let violation_count = references
+        .iter()
+        .filter(|reference| !reference.violations.is_empty())
+        .count();
+    let _ = writeln!(
+        out,
+        "\n{} reference(s), {} with violations",
+        references.len(),
+        violation_count
+    );
+    out
+}
+
+// e.g. " [dependency violation (recorded), privacy violation (strict)]"
+fn violation_summary(violations: &[ReferenceViolation]) -> String {
+    if violations.is_empty() {
+        return String::new();
+    }
+    let descriptions: Vec<String> = violations
+        .iter()
+        .map(|violation| {
+            let mut description =
+                format!("{} violation", violation.violation_type);
+            if violation.strict {
+                description.push_str(" (strict)");
+            } else if violation.recorded {
+                description.push_str(" (recorded)");
+            }
+            description
+        })
+        .collect();
+    format!(" [{}]", descriptions.join(", "))
+}
@@ -0,0 +1,186 @@
+//! Ruby native extension, built with the `ruby` feature.
+//!
+//! Defines a `Pks` module so Ruby tooling (rake tasks, RuboCop cops) can run
+//! pks in-process instead of spawning the binary. Every function takes the
+//! application root; results are hashes and arrays mirroring the JSON
+//! output, and errors are raised as `RuntimeError`s.
+//!
+//! ```ruby
+//! require "pks"
+//!
+//! result = Pks.check(Rails.root.to_s)
+//! result["violations"].each { |v| puts v["message"] }
+//! Pks.pack_for_file(Rails.root.to_s, "packs/foo/app/models/foo.rb")["name"]
+//! ```
+
+use std::path::PathBuf;
+
+use magnus::{
+    function, prelude::*, scan_args::scan_args, Error, RArray, RHash, Ruby,
+    Value,
+};
+use serde_json::json;
+
+use super::checker;
+use super::pack::Pack;
+use super::{configuration, constant_definitions, json, Configuration};
+
+#[magnus::init(name = "pks")]
+fn init(ruby: &Ruby) -> Result<(), Error> {
+    let module = ruby.define_module("Pks")?;
+    module.define_module_function("check", function!(check, -1))?;
+    module.define_module_function("update", function!(update, 1))?;
+    module.define_module_function("validate", function!(validate, 1))?;
+    module.define_module_function(
+        "list_definitions",
+        function!(list_definitions, -1),
+    )?;
+    module.define_module_function("packs", function!(packs, 1))?;
+    module.define_module_function("pack", function!(pack, 2))?;
+    module
+        .define_module_function("pack_for_file", function!(pack_for_file, 2))?;
+    Ok(())
+}
+
+/// `Pks.check(root, files = [])`: the `pks check -o json` output as a hash.
+fn check(ruby: &Ruby, args: &[Value]) -> Result<Value, Error> {
+    let args =
+        scan_args::<(String,), (Option<Vec<String>>,), (), (), (), ()>(args)?;
+    let (root,) = args.required;
+    let (files,) = args.optional;
+    let configuration = load(ruby, root)?;
+    let result = checker::check_all(&configuration, files.unwrap_or_default())
+        .map_err(|err| to_ruby_error(ruby, err))?;
+    let output = json::to_value(&result, &configuration)
+        .map_err(|err| to_ruby_error(ruby, err))?;
+    to_ruby(ruby, &output)
+}
+
+/// `Pks.update(root)`: rewrites the package_todo.yml files, like `pks update`.
+fn update(ruby: &Ruby, root: String) -> Result<(), Error> {
+    let configuration = load(ruby, root)?;
+    checker::update(&configuration).map_err(|err| to_ruby_error(ruby, err))
+}
+
+/// `Pks.validate(root)`: the validation errors, empty if there are none.
+fn validate(ruby: &Ruby, root: String) -> Result<Vec<String>, Error> {
+    let configuration = load(ruby, root)?;
+    Ok(checker::validate(&configuration))
+}
+
+/// `Pks.list_definitions(root, ambiguous = false)`: a hash of constant names
+/// to the files defining them.
+fn list_definitions(ruby: &Ruby, args: &[Value]) -> Result<RHash, Error> {
+    let args = scan_args::<(String,), (Option<bool>,), (), (), (), ()>(args)?;
+    let (root,) = args.required;
+    let (ambiguous,) = args.optional;
+    let configuration = load(ruby, root)?;
+    let definitions =
+        constant_definitions(&configuration, ambiguous.unwrap_or(false))
+            .map_err(|err| to_ruby_error(ruby, err))?;
+
+    let hash = ruby.hash_new();
+    for (name, relative_paths) in definitions {
+        let paths: Vec<String> = relative_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        hash.aset(name, paths)?;
+    }
+    Ok(hash)
+}
+
+/// `Pks.packs(root)`: every pack, as hashes.
+fn packs(ruby: &Ruby, root: String) -> Result<RArray, Error> {
+    let configuration = load(ruby, root)?;
+    // Built as a Ruby array rather than a Vec, so the GC can see the packs
+    let array = ruby.ary_new();
+    for pack in &configuration.pack_set.packs {
+        array.push(to_ruby(ruby, &pack_json(pack))?)?;
+    }
+    Ok(array)
+}
+
+/// `Pks.pack(root, name)`: the pack with that name; raises if there is none.
+fn pack(ruby: &Ruby, root: String, name: String) -> Result<Value, Error> {
+    let configuration = load(ruby, root)?;
+    let pack = configuration
+        .pack_set
+        .for_pack(&name)
+        .map_err(|err| to_ruby_error(ruby, err))?;
+    to_ruby(ruby, &pack_json(pack))
+}
+
+/// `Pks.pack_for_file(root, file)`: the pack owning `file` (absolute or
+/// relative to the root), or nil.
+fn pack_for_file(
+    ruby: &Ruby,
+    root: String,
+    file: String,
+) -> Result<Option<Value>, Error> {
+    let configuration = load(ruby, root)?;
+    let absolute_path = configuration.absolute_root.join(file);
+    let pack = configuration
+        .pack_set
+        .for_file(&absolute_path)
+        .map_err(|err| to_ruby_error(ruby, err))?;
+    pack.map(|pack| to_ruby(ruby, &pack_json(pack))).transpose()
+}
+
+fn load(ruby: &Ruby, root: String) -> Result<Configuration, Error> {
+    PathBuf::from(root)
+        .canonicalize()
+        .map_err(anyhow::Error::from)
+        .and_then(|absolute_root| configuration::get(&absolute_root))
+        .map_err(|err| to_ruby_error(ruby, err))
+}
+
+fn pack_json(pack: &Pack) -> serde_json::Value {
+    let mut dependencies: Vec<&String> = pack.dependencies.iter().collect();
+    dependencies.sort();
+    let visible_to = pack.visible_to.as_ref().map(|visible_to| {
+        let mut visible_to: Vec<&String> = visible_to.iter().collect();
+        visible_to.sort();
+        visible_to
+    });
+    json!({
+        "name": pack.name,
+        "relative_path": pack.relative_path,
+        "yml": pack.relative_yml(),
+        "dependencies": dependencies,
+        "layer": pack.layer,
+        "owner": pack.owner,
+        "visible_to": visible_to,
+    })
+}
+
+fn to_ruby(ruby: &Ruby, value: &serde_json::Value) -> Result<Value, Error> {
+    Ok(match value {
+        serde_json::Value::Null => ruby.qnil().as_value(),
+        serde_json::Value::Bool(b) => ruby.into_value(*b),
+        serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => ruby.into_value(i),
+            (None, Some(u)) => ruby.into_value(u),
+            (None, None) => ruby.into_value(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => ruby.into_value(s.as_str()),
+        serde_json::Value::Array(items) => {
+            let array = ruby.ary_new();
+            for item in items {
+                array.push(to_ruby(ruby, item)?)?;
+            }
+            array.as_value()
+        }
+        serde_json::Value::Object(map) => {
+            let hash = ruby.hash_new();
+            for (key, item) in map {
+                hash.aset(key.as_str(), to_ruby(ruby, item)?)?;
+            }
+            hash.as_value()
+        }
+    })
+}
+
+fn to_ruby_error(ruby: &Ruby, err: anyhow::Error) -> Error {
+    Error::new(ruby.exception_runtime_error(), format!("{:#}", err))
+}
@@ -0,0 +1,263 @@
+//! SARIF output formatter for `pks check -o sarif`.
+//!
+//! Writes a SARIF 2.1.0 log for code-scanning tools, with one rule per
+//! checker and one result per violation. Stale TODOs have no location in
+//! the code, so they are not part of the log. Parse errors are problems
+//! with the analysis rather than results, so they are reported as tool
+//! execution notifications.
+//! See https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::checker::{CheckAllResult, ParseError, Violation};
+use super::checker_configuration::CheckerConfiguration;
+use super::json::build_message;
+use super::Configuration;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifRun<'a> {
+    tool: SarifTool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    invocations: Vec<SarifInvocation<'a>>,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifInvocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<SarifNotification<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifNotification<'a> {
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: String,
+    name: String,
+    short_description: SarifMessage,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation<'a>>,
+    properties: SarifProperties<'a>,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation<'a> {
+    physical_location: SarifPhysicalLocation<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation<'a> {
+    artifact_location: SarifArtifactLocation<'a>,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation<'a> {
+    uri: &'a str,
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    start_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifProperties<'a> {
+    constant_name: &'a str,
+    referencing_pack_name: &'a str,
+    defining_pack_name: &'a str,
+    strict: bool,
+}
+
+fn build_rule(checker_config: &CheckerConfiguration) -> SarifRule {
+    let pretty_name = checker_config.pretty_checker_name();
+    SarifRule {
+        id: checker_config.checker_name(),
+        name: format!("{}Violation", pretty_name.replace(' ', "")),
+        short_description: SarifMessage {
+            text: format!("{} violation", pretty_name),
+        },
+    }
+}
+
+fn build_result<'a>(
+    v: &'a Violation,
+    config: &Configuration,
+    rule_ids: &[String],
+) -> SarifResult<'a> {
+    let rule_id = v.identifier.violation_type.to_string();
+    let rule_index = rule_ids
+        .iter()
+        .position(|id| *id == rule_id)
+        .expect("every checker type has a rule");
+    SarifResult {
+        rule_id,
+        rule_index,
+        level: "error",
+        message: SarifMessage {
+            text: build_message(v, config),
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: &v.identifier.file,
+                    uri_base_id: "%SRCROOT%",
+                },
+                region: SarifRegion {
+                    start_line: v.source_location.line,
+                    // SARIF columns are 1-based, ours are 0-based
+                    start_column: v.source_location.column + 1,
+                },
+            },
+        }],
+        properties: SarifProperties {
+            constant_name: &v.identifier.constant_name,
+            referencing_pack_name: &v.identifier.referencing_pack_name,
+            defining_pack_name: &v.identifier.defining_pack_name,
+            strict: v.identifier.strict,
+        },
+    }
+}
+
+fn build_notification<'a>(
+    parse_error: &'a ParseError,
+    level: &'static str,
+) -> SarifNotification<'a> {
+    SarifNotification {
+        level,
+        message: SarifMessage {
+            text: parse_error.message.clone(),
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: &parse_error.file,
+                    uri_base_id: "%SRCROOT%",
+                },
+                region: SarifRegion {
+                    start_line: parse_error.line,
+                    start_column: parse_error.column + 1,
+                },
+            },
+        }],
+    }
+}
+
+pub fn write_sarif<W: std::io::Write>(
+    result: &CheckAllResult,
+    config: &Configuration,
+    writer: W,
+) -> anyhow::Result<()> {
+    let rules: Vec<SarifRule> = config
+        .checker_configuration
+        .values()
+        .sorted_by_key(|checker_config| checker_config.checker_name())
+        .map(build_rule)
+        .collect();
+    let rule_ids: Vec<String> =
+        rules.iter().map(|rule| rule.id.clone()).collect();
+
+    let results: Vec<SarifResult> = result
+        .sorted_violations()
+        .into_iter()
+        .map(|v| build_result(v, config, &rule_ids))
+        .collect();
+
+    // Invalid `# pks:` comments always fail the check
+    let invocations = if result.parse_errors.is_empty()
+        && result.invalid_suppressions.is_empty()
+    {
+        vec![]
+    } else {
+        let fails = result.fails_on_parse_errors(config);
+        let level = if fails { "error" } else { "warning" };
+        vec![SarifInvocation {
+            execution_successful: !fails
+                && result.invalid_suppressions.is_empty(),
+            tool_execution_notifications: result
+                .parse_errors
+                .iter()
+                .map(|e| build_notification(e, level))
+                .chain(
+                    result
+                        .invalid_suppressions
+                        .iter()
+                        .map(|e| build_notification(e, "error")),
+                )
+                .collect(),
+        }]
+    };
+
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "pks",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            invocations,
+            results,
+        }],
+    };
+
+    serde_json::to_writer(writer, &log)?;
+    Ok(())
+}
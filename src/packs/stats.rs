@@ -0,0 +1,514 @@
+//! Violation metrics for `pks stats`.
+//!
+//! Aggregates the violations recorded in package_todo.yml files together
+//! with the ones `check` finds that are not recorded yet, per referencing
+//! pack, checker type, owner and defining pack. Alongside the counts, each
+//! pack reports the checkers it enforces in strict mode and how many of its
+//! files are in its public folder.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::checker::{self, CheckAllResult};
+use super::checker_configuration::CheckerType;
+use super::cli::StatsFormat;
+use super::pack::{CheckerSetting, Pack};
+use super::{Configuration, ViolationIdentifier};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    /// Recorded in package_todo.yml files
+    pub recorded: usize,
+    /// Found by `check` but not recorded
+    pub new: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ViolationCounts {
+    pub recorded: usize,
+    pub new: usize,
+    pub by_type: BTreeMap<String, Counts>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PackStats {
+    pub name: String,
+    pub owner: Option<String>,
+    pub layer: Option<String>,
+    /// Checkers this pack enforces in strict mode
+    pub strict: Vec<String>,
+    pub files: usize,
+    pub public_files: usize,
+    /// `public_files / files`, or `None` for a pack without files
+    pub public_api_ratio: Option<f64>,
+    /// Violations in this pack's files
+    pub violations: ViolationCounts,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct OwnerStats {
+    pub owner: Option<String>,
+    pub packs: usize,
+    pub violations: ViolationCounts,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DefiningPackStats {
+    pub name: String,
+    /// Violations on constants this pack defines
+    pub violations: ViolationCounts,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Totals {
+    pub recorded: usize,
+    pub new: usize,
+    pub stale: usize,
+    pub strict_mode: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    pub totals: Totals,
+    pub checker_types: BTreeMap<String, Counts>,
+    pub packs: Vec<PackStats>,
+    pub owners: Vec<OwnerStats>,
+    pub defining_packs: Vec<DefiningPackStats>,
+}
+
+impl Counts {
+    fn add(&mut self, recorded: bool) {
+        if recorded {
+            self.recorded += 1;
+        } else {
+            self.new += 1;
+        }
+    }
+}
+
+impl ViolationCounts {
+    fn add(&mut self, violation_type: &CheckerType, recorded: bool) {
+        if recorded {
+            self.recorded += 1;
+        } else {
+            self.new += 1;
+        }
+        self.by_type
+            .entry(violation_type.to_string())
+            .or_default()
+            .add(recorded);
+    }
+}
+
+pub fn build_stats(configuration: &Configuration) -> anyhow::Result<Stats> {
+    let result = checker::check_all(configuration, vec![])?;
+    let recorded: Vec<ViolationIdentifier> = configuration
+        .pack_set
+        .packs
+        .iter()
+        .flat_map(|pack| pack.all_violations())
+        .collect();
+
+    let mut file_counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for path in &configuration.included_files {
+        if let Some(pack) = configuration.pack_set.for_file(path)? {
+            let public_folder =
+                configuration.absolute_root.join(pack.public_folder());
+            let counts = file_counts.entry(&pack.name).or_default();
+            counts.0 += 1;
+            if path.starts_with(&public_folder) {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    Ok(aggregate(
+        &configuration.pack_set.packs,
+        &recorded,
+        &result,
+        &file_counts,
+    ))
+}
+
+// `file_counts` maps pack names to their number of files and public files.
+fn aggregate(
+    packs: &[Pack],
+    recorded: &[ViolationIdentifier],
+    result: &CheckAllResult,
+    file_counts: &HashMap<&str, (usize, usize)>,
+) -> Stats {
+    // Strict mode violations are reported whether or not they are recorded,
+    // and are only new if they are not. Like recorded violations, a new one
+    // counts once however many times its constant is referenced in the file.
+    let recorded_set: HashSet<&ViolationIdentifier> = recorded.iter().collect();
+    let new: HashSet<&ViolationIdentifier> = result
+        .reportable_violations
+        .iter()
+        .map(|v| &v.identifier)
+        .filter(|identifier| {
+            let recorded_identifier = ViolationIdentifier {
+                strict: false,
+                ..(*identifier).clone()
+            };
+            !recorded_set.contains(&recorded_identifier)
+        })
+        .collect();
+    let identifiers = recorded
+        .iter()
+        .map(|id| (id, true))
+        .chain(new.iter().map(|id| (*id, false)));
+
+    let owners: HashMap<&str, Option<&String>> = packs
+        .iter()
+        .map(|pack| (pack.name.as_str(), pack.owner.as_ref()))
+        .collect();
+    let mut checker_types: BTreeMap<String, Counts> = BTreeMap::new();
+    let mut by_pack: HashMap<&str, ViolationCounts> = HashMap::new();
+    // Owners whose packs have no violations still count towards progress
+    let mut by_owner: BTreeMap<Option<&String>, (usize, ViolationCounts)> =
+        BTreeMap::new();
+    for pack in packs {
+        by_owner.entry(pack.owner.as_ref()).or_default().0 += 1;
+    }
+    let mut by_defining_pack: BTreeMap<&str, ViolationCounts> = BTreeMap::new();
+    for (identifier, is_recorded) in identifiers {
+        let violation_type = &identifier.violation_type;
+        checker_types
+            .entry(violation_type.to_string())
+            .or_default()
+            .add(is_recorded);
+        by_pack
+            .entry(&identifier.referencing_pack_name)
+            .or_default()
+            .add(violation_type, is_recorded);
+        let owner = owners
+            .get(identifier.referencing_pack_name.as_str())
+            .copied()
+            .flatten();
+        by_owner
+            .entry(owner)
+            .or_default()
+            .1
+            .add(violation_type, is_recorded);
+        by_defining_pack
+            .entry(&identifier.defining_pack_name)
+            .or_default()
+            .add(violation_type, is_recorded);
+    }
+
+    let mut pack_stats: Vec<PackStats> = packs
+        .iter()
+        .map(|pack| {
+            let (files, public_files) = file_counts
+                .get(pack.name.as_str())
+                .copied()
+                .unwrap_or_default();
+            PackStats {
+                name: pack.name.clone(),
+                owner: pack.owner.clone(),
+                layer: pack.layer.clone(),
+                strict: strict_checkers(pack),
+                files,
+                public_files,
+                public_api_ratio: (files > 0)
+                    .then(|| public_files as f64 / files as f64),
+                violations: by_pack
+                    .remove(pack.name.as_str())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+    pack_stats.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Stats {
+        totals: Totals {
+            recorded: recorded.len(),
+            new: new.len(),
+            stale: result.stale_violations.len(),
+            strict_mode: result.strict_mode_violations.len(),
+        },
+        checker_types,
+        packs: pack_stats,
+        owners: by_owner
+            .into_iter()
+            .map(|(owner, (packs, violations))| OwnerStats {
+                owner: owner.cloned(),
+                packs,
+                violations,
+            })
+            .collect(),
+        defining_packs: by_defining_pack
+            .into_iter()
+            .map(|(name, violations)| DefiningPackStats {
+                name: name.to_string(),
+                violations,
+            })
+            .collect(),
+    }
+}
+
+fn strict_checkers(pack: &Pack) -> Vec<String> {
+    [
+        (CheckerType::Dependency, &pack.enforce_dependencies),
+        (CheckerType::Privacy, &pack.enforce_privacy),
+        (
+            CheckerType::FolderPrivacy,
+            &Some(pack.enforce_folder_privacy().clone()),
+        ),
+        (CheckerType::Layer, &pack.enforce_layers),
+        (CheckerType::Visibility, &pack.enforce_visibility),
+    ]
+    .into_iter()
+    .filter(|(_, setting)| matches!(setting, Some(CheckerSetting::Strict)))
+    .map(|(checker_type, _)| checker_type.to_string())
+    .collect()
+}
+
+impl Stats {
+    pub fn render(&self, format: StatsFormat) -> anyhow::Result<String> {
+        match format {
+            StatsFormat::Table => Ok(self.to_table()),
+            StatsFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} recorded, {} new, {} stale and {} strict mode violation(s)",
+            self.totals.recorded,
+            self.totals.new,
+            self.totals.stale,
+            self.totals.strict_mode
+        );
+
+        out.push('\n');
+        out.push_str(&table(
+            &["Checker", "Recorded", "New"],
+            self.checker_types
+                .iter()
+                .map(|(checker_type, counts)| {
+                    vec![
+                        checker_type.clone(),
+                        counts.recorded.to_string(),
+                        counts.new.to_string(),
+                    ]
+                })
+                .collect(),
+        ));
+
+        out.push('\n');
+        out.push_str(&table(
+            &["Pack", "Owner", "Recorded", "New", "Strict", "Public API"],
+            self.packs
+                .iter()
+                .map(|pack| {
+                    let public_api = match pack.public_api_ratio {
+                        Some(ratio) => format!(
+                            "{}/{} ({:.0}%)",
+                            pack.public_files,
+                            pack.files,
+                            ratio * 100.0
+                        ),
+                        None => "-".to_string(),
+                    };
+                    vec![
+                        pack.name.clone(),
+                        owner_name(&pack.owner),
+                        pack.violations.recorded.to_string(),
+                        pack.violations.new.to_string(),
+                        if pack.strict.is_empty() {
+                            "-".to_string()
+                        } else {
+                            pack.strict.join(", ")
+                        },
+                        public_api,
+                    ]
+                })
+                .collect(),
+        ));
+
+        out.push('\n');
+        out.push_str(&table(
+            &["Owner", "Packs", "Recorded", "New"],
+            self.owners
+                .iter()
+                .map(|owner| {
+                    vec![
+                        owner_name(&owner.owner),
+                        owner.packs.to_string(),
+                        owner.violations.recorded.to_string(),
+                        owner.violations.new.to_string(),
+                    ]
+                })
+                .collect(),
+        ));
+
+        out.push('\n');
+        out.push_str(&table(
+            &["Defining pack", "Recorded", "New"],
+            self.defining_packs
+                .iter()
+                .map(|pack| {
+                    vec![
+                        pack.name.clone(),
+                        pack.violations.recorded.to_string(),
+                        pack.violations.new.to_string(),
+                    ]
+                })
+                .collect(),
+        ));
+        out
+    }
+}
+
+fn owner_name(owner: &Option<String>) -> String {
+    owner.clone().unwrap_or_else(|| "-".to_string())
+}
+
+// Left-aligned columns separated by two spaces, without trailing whitespace.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        let _ = writeln!(out, "{}", line.join("  ").trim_end());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::packs::checker::Violation;
+    use crate::packs::package_todo::PackageTodo;
+    use crate::packs::SourceLocation;
+
+    fn pack(name: &str, package_yml: &str) -> Pack {
+        let root = PathBuf::from("/app");
+        Pack::from_contents(
+            &root.join(name).join("package.yml"),
+            &root,
+            package_yml,
+            PackageTodo::default(),
+        )
+        .unwrap()
+    }
+
+    fn identifier(
+        violation_type: CheckerType,
+        file: &str,
+        from: &str,
+        to: &str,
+    ) -> ViolationIdentifier {
+        ViolationIdentifier {
+            violation_type,
+            strict: false,
+            file: file.to_string(),
+            constant_name: "::Bar".to_string(),
+            referencing_pack_name: from.to_string(),
+            defining_pack_name: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let packs = vec![
+            pack("packs/foo", "owner: Team A\nenforce_privacy: strict\n"),
+            pack("packs/bar", ""),
+            pack("packs/baz", ""),
+        ];
+        let recorded = vec![
+            identifier(
+                CheckerType::Dependency,
+                "a.rb",
+                "packs/foo",
+                "packs/bar",
+            ),
+            identifier(CheckerType::Privacy, "a.rb", "packs/foo", "packs/bar"),
+        ];
+        let result = CheckAllResult {
+            reportable_violations: HashSet::from([Violation {
+                identifier: identifier(
+                    CheckerType::Dependency,
+                    "b.rb",
+                    "packs/baz",
+                    "packs/bar",
+                ),
+                source_location: SourceLocation { line: 1, column: 0 },
+                referencing_pack_relative_yml: String::new(),
+                defining_layer: None,
+                referencing_layer: None,
+            }]),
+            stale_violations: vec![],
+            strict_mode_violations: HashSet::new(),
+            parse_errors: vec![],
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
+        };
+        let file_counts = HashMap::from([("packs/foo", (4, 1))]);
+
+        let stats = aggregate(&packs, &recorded, &result, &file_counts);
+
+        assert_eq!(
+            stats.totals,
+            Totals {
+                recorded: 2,
+                new: 1,
+                stale: 0,
+                strict_mode: 0
+            }
+        );
+        assert_eq!(
+            stats.checker_types["dependency"],
+            Counts {
+                recorded: 1,
+                new: 1
+            }
+        );
+        let foo = &stats.packs[2];
+        assert_eq!(foo.name, "packs/foo");
+        assert_eq!(foo.strict, vec!["privacy"]);
+        assert_eq!(foo.public_api_ratio, Some(0.25));
+        assert_eq!(foo.violations.recorded, 2);
+        assert_eq!(stats.packs[0].public_api_ratio, None);
+        assert_eq!(
+            stats
+                .owners
+                .iter()
+                .map(|o| (o.owner.clone(), o.packs, o.violations.new))
+                .collect::<Vec<_>>(),
+            vec![(None, 2, 1), (Some("Team A".to_string()), 1, 0)]
+        );
+        assert_eq!(stats.defining_packs.len(), 1);
+        assert_eq!(stats.defining_packs[0].violations.recorded, 2);
+        assert_eq!(stats.defining_packs[0].violations.new, 1);
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            table(
+                &["Pack", "New"],
+                vec![vec!["packs/foo".to_string(), "1".to_string()]]
+            ),
+            "Pack       New\npacks/foo  1\n"
+        );
+    }
+}
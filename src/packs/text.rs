@@ -4,7 +4,7 @@
 
 use super::bin_locater;
 use super::checker::{
-    build_strict_violation_message, CheckAllResult, Violation,
+    build_strict_violation_message, CheckAllResult, ParseError, Violation,
 };
 use super::template::{
     build_violation_vars, colorize_reference_location, expand,
@@ -19,7 +19,7 @@ pub enum ColorMode {
 }
 
 /// Format a violation message with optional colorization of the location.
-fn format_violation_message(
+pub(crate) fn format_violation_message(
     violation: &Violation,
     config: &Configuration,
     color_mode: ColorMode,
@@ -41,14 +41,68 @@ fn format_violation_message(
     expand(&template, &vars)
 }
 
+fn write_parse_errors<W: std::io::Write>(
+    parse_errors: &[ParseError],
+    writer: &mut W,
+    color_mode: ColorMode,
+) -> anyhow::Result<()> {
+    if parse_errors.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        writer,
+        "{} parse error(s) detected, violations in these files may be missing:",
+        parse_errors.len()
+    )?;
+    write_located_messages(parse_errors, writer, color_mode)
+}
+
+fn write_invalid_suppressions<W: std::io::Write>(
+    invalid_suppressions: &[ParseError],
+    writer: &mut W,
+    color_mode: ColorMode,
+) -> anyhow::Result<()> {
+    if invalid_suppressions.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        writer,
+        "{} invalid `# pks:` comment(s) detected:",
+        invalid_suppressions.len()
+    )?;
+    write_located_messages(invalid_suppressions, writer, color_mode)
+}
+
+fn write_located_messages<W: std::io::Write>(
+    errors: &[ParseError],
+    writer: &mut W,
+    color_mode: ColorMode,
+) -> anyhow::Result<()> {
+    for error in errors {
+        let mut location =
+            format!("{}:{}:{}", error.file, error.line, error.column);
+        if color_mode == ColorMode::Colored {
+            location = colorize_reference_location(&location);
+        }
+        writeln!(writer, "{}\n{}\n", location, error.message)?;
+    }
+    Ok(())
+}
+
 pub fn write_text<W: std::io::Write>(
     result: &CheckAllResult,
     config: &Configuration,
     mut writer: W,
     color_mode: ColorMode,
 ) -> anyhow::Result<()> {
+    write_parse_errors(&result.parse_errors, &mut writer, color_mode)?;
+
     if !result.has_violations() {
-        writeln!(writer, "No violations detected!")?;
+        if !result.fails_on_parse_errors(config) {
+            writeln!(writer, "No violations detected!")?;
+        }
         return Ok(());
     }
 
@@ -84,6 +138,12 @@ pub fn write_text<W: std::io::Write>(
         }
     }
 
+    write_invalid_suppressions(
+        &result.invalid_suppressions,
+        &mut writer,
+        color_mode,
+    )?;
+
     Ok(())
 }
 
@@ -122,6 +182,9 @@ mod tests {
             reportable_violations: HashSet::new(),
             stale_violations: Vec::new(),
             strict_mode_violations: HashSet::new(),
+            parse_errors: Vec::new(),
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
         };
 
         let mut output = Vec::new();
@@ -139,6 +202,9 @@ mod tests {
             reportable_violations: [sample_violation()].into_iter().collect(),
             stale_violations: Vec::new(),
             strict_mode_violations: HashSet::new(),
+            parse_errors: Vec::new(),
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
         };
 
         let mut output = Vec::new();
@@ -157,6 +223,9 @@ mod tests {
             reportable_violations: [sample_violation()].into_iter().collect(),
             stale_violations: Vec::new(),
             strict_mode_violations: HashSet::new(),
+            parse_errors: Vec::new(),
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
         };
 
         let mut output = Vec::new();
@@ -165,4 +234,58 @@ mod tests {
         // Check that ANSI codes are present for the location
         assert!(text.contains("\x1b[36mfoo/bar/file.rb:10:5\x1b[0m"));
     }
+
+    #[test]
+    fn test_write_text_with_parse_errors() {
+        let config = Configuration::default();
+        let result = CheckAllResult {
+            reportable_violations: HashSet::new(),
+            stale_violations: Vec::new(),
+            strict_mode_violations: HashSet::new(),
+            parse_errors: vec![ParseError {
+                file: "foo/bar/file.rb".to_string(),
+                line: 3,
+                column: 2,
+                message: "unexpected end-of-input".to_string(),
+            }],
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
+        };
+
+        let mut output = Vec::new();
+        write_text(&result, &config, &mut output, ColorMode::Plain).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1 parse error(s) detected, violations in these files may be missing:\n\
+             foo/bar/file.rb:3:2\nunexpected end-of-input\n\n\
+             No violations detected!\n"
+        );
+    }
+
+    #[test]
+    fn test_write_text_with_invalid_suppressions() {
+        let config = Configuration::default();
+        let result = CheckAllResult {
+            reportable_violations: HashSet::new(),
+            stale_violations: Vec::new(),
+            strict_mode_violations: HashSet::new(),
+            parse_errors: vec![],
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![ParseError {
+                file: "foo/bar/file.rb".to_string(),
+                line: 3,
+                column: 4,
+                message: "`# pks:enable-block` without a matching `# pks:disable-block`".to_string(),
+            }],
+        };
+
+        let mut output = Vec::new();
+        write_text(&result, &config, &mut output, ColorMode::Plain).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1 invalid `# pks:` comment(s) detected:\n\
+             foo/bar/file.rb:3:4\n\
+             `# pks:enable-block` without a matching `# pks:disable-block`\n\n"
+        );
+    }
 }
@@ -0,0 +1,460 @@
+//! Watch mode for `pks check --watch`.
+//!
+//! Keeps the processed files, resolved references and constant resolver in
+//! memory between runs, so that a change only costs re-parsing the files
+//! that were touched. Changes to `package.yml`, `package_todo.yml` or
+//! `packwerk.yml` reload the configuration (and with it the `PackSet`).
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{Event, RecursiveMode, Watcher};
+use tracing::debug;
+
+use super::checker::reference::Reference;
+use super::checker::{
+    check_references, invalid_suppressions_for, parse_errors_for,
+    CheckAllResult, ViolationIdentifier,
+};
+use super::configuration::{self, Configuration};
+use super::constant_resolver::ConstantResolver;
+use super::file_utils::get_file_type;
+use super::reference_extractor::resolve_references;
+use super::{
+    get_experimental_constant_resolver, get_zeitwerk_constant_resolver,
+    process_files_with_cache, ProcessedFile,
+};
+
+// Editors tend to write a file in several steps; wait for them to settle.
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Checks `files` (or everything, if empty), then re-checks after every
+/// relevant change on disk until the process is interrupted. `report` is
+/// called with each result and a one-line summary of what changed.
+pub(crate) fn watch<F>(
+    configuration: Configuration,
+    files: Vec<String>,
+    mut report: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&CheckAllResult, &Configuration, &str) -> anyhow::Result<()>,
+{
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<Event>| {
+            match event {
+                // Reading a file (as checking does) is not a change
+                Ok(event) if event.kind.is_access() => {}
+                event => {
+                    let _ = sender.send(event);
+                }
+            }
+        })?;
+    watcher.watch(&configuration.absolute_root, RecursiveMode::Recursive)?;
+
+    let mut session = WatchSession::new(configuration, files)?;
+    let mut previous = session.check()?;
+    report(
+        &previous,
+        &session.configuration,
+        &summarize(None, &previous),
+    )?;
+
+    while let Some(paths) = next_changes(&receiver) {
+        // A half-written package.yml or a file that does not parse yet should
+        // not end the session; report it and wait for the next change.
+        let result = match session.apply_changes(&paths) {
+            Ok(false) => continue,
+            Ok(true) => session.check(),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(result) => {
+                let summary = summarize(Some(&previous), &result);
+                report(&result, &session.configuration, &summary)?;
+                previous = result;
+            }
+            Err(err) => eprintln!("Error: {err:#}"),
+        }
+    }
+
+    Ok(())
+}
+
+// Blocks until something changes, then collects everything that changes
+// until things settle. Returns None once the watcher has gone away.
+fn next_changes(
+    receiver: &mpsc::Receiver<notify::Result<Event>>,
+) -> Option<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut event = receiver.recv().ok()?;
+    loop {
+        match event {
+            Ok(event) => paths.extend(event.paths),
+            Err(err) => eprintln!("Error watching files: {}", err),
+        }
+        match receiver.recv_timeout(DEBOUNCE_TIMEOUT) {
+            Ok(next_event) => event = next_event,
+            Err(_) => return Some(paths),
+        }
+    }
+}
+
+pub(crate) struct WatchSession {
+    configuration: Configuration,
+    files: Vec<String>,
+    // The files being checked
+    absolute_paths: HashSet<PathBuf>,
+    // The experimental parser needs *all* processed files to get definitions,
+    // so this holds every included file in that case
+    processed_files: HashMap<PathBuf, ProcessedFile>,
+    references: Vec<Reference>,
+    constant_resolver: Box<dyn ConstantResolver + Send + Sync>,
+}
+
+impl WatchSession {
+    pub(crate) fn new(
+        configuration: Configuration,
+        files: Vec<String>,
+    ) -> anyhow::Result<WatchSession> {
+        // A placeholder that is cheap to build; `rebuild` replaces it once
+        // the files have been processed.
+        let constant_resolver = get_experimental_constant_resolver(
+            &configuration.absolute_root,
+            &Vec::new(),
+            &configuration.ignored_definitions,
+        );
+        let mut session = WatchSession {
+            configuration,
+            files,
+            absolute_paths: HashSet::new(),
+            processed_files: HashMap::new(),
+            references: Vec::new(),
+            constant_resolver,
+        };
+        session.rebuild()?;
+        Ok(session)
+    }
+
+    pub(crate) fn check(&self) -> anyhow::Result<CheckAllResult> {
+        let checked_files = || {
+            self.absolute_paths
+                .iter()
+                .filter_map(|path| self.processed_files.get(path))
+        };
+        check_references(
+            &self.configuration,
+            self.absolute_paths.clone(),
+            &self.references,
+            parse_errors_for(&self.configuration, checked_files()),
+            invalid_suppressions_for(&self.configuration, checked_files()),
+        )
+    }
+
+    /// Brings the session up to date with `paths` having changed on disk.
+    /// Returns false if none of them affect the check.
+    pub(crate) fn apply_changes(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> anyhow::Result<bool> {
+        let mut modified_files = HashSet::new();
+        let mut needs_reload = false;
+
+        for path in paths {
+            if self.is_ignored(path) {
+                continue;
+            }
+            if configuration::is_configuration_file(path) {
+                needs_reload = true;
+            } else if self.configuration.included_files.contains(path) {
+                if path.exists() {
+                    modified_files.insert(path.clone());
+                } else {
+                    needs_reload = true;
+                }
+            } else if path.is_file() && get_file_type(path).is_some() {
+                // Only a walk of the directory can tell whether a new file
+                // is included
+                needs_reload = true;
+            }
+        }
+
+        if needs_reload {
+            debug!("Reloading configuration");
+            self.configuration = self.configuration.reload()?;
+            self.rebuild()?;
+            Ok(true)
+        } else if !modified_files.is_empty() {
+            debug!("Re-checking {} modified file(s)", modified_files.len());
+            self.process(&modified_files)?;
+            if self.configuration.experimental_parser {
+                // Definitions may have moved, which can change how any
+                // reference resolves
+                self.constant_resolver = self.constant_resolver_for_session();
+                self.resolve(&self.absolute_paths.clone())?;
+            } else {
+                let modified_files = modified_files
+                    .intersection(&self.absolute_paths)
+                    .cloned()
+                    .collect();
+                self.resolve(&modified_files)?;
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // Re-derives everything from the current configuration. Files that are
+    // still included and unchanged are not parsed again.
+    fn rebuild(&mut self) -> anyhow::Result<()> {
+        self.absolute_paths =
+            self.configuration.intersect_files(self.files.clone());
+        let files_to_process = self.files_to_process();
+        self.processed_files
+            .retain(|path, _| files_to_process.contains(path));
+        let unprocessed_files = files_to_process
+            .into_iter()
+            .filter(|path| !self.processed_files.contains_key(path))
+            .collect();
+        self.process(&unprocessed_files)?;
+
+        self.constant_resolver = self.constant_resolver_for_session();
+        self.references.clear();
+        self.resolve(&self.absolute_paths.clone())
+    }
+
+    fn files_to_process(&self) -> HashSet<PathBuf> {
+        if self.configuration.experimental_parser {
+            self.configuration.included_files.clone()
+        } else {
+            self.absolute_paths.clone()
+        }
+    }
+
+    fn process(&mut self, paths: &HashSet<PathBuf>) -> anyhow::Result<()> {
+        let paths = paths
+            .intersection(&self.files_to_process())
+            .cloned()
+            .collect();
+        let processed_files = process_files_with_cache(
+            &paths,
+            self.configuration.get_cache(),
+            &self.configuration,
+        )?;
+        for processed_file in processed_files {
+            self.processed_files
+                .insert(processed_file.absolute_path.clone(), processed_file);
+        }
+        Ok(())
+    }
+
+    // Replaces the references of `paths` with freshly resolved ones
+    fn resolve(&mut self, paths: &HashSet<PathBuf>) -> anyhow::Result<()> {
+        let relative_paths: HashSet<String> = paths
+            .iter()
+            .filter_map(|path| {
+                path.strip_prefix(&self.configuration.absolute_root).ok()
+            })
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        self.references.retain(|reference| {
+            !relative_paths.contains(&reference.relative_referencing_file)
+        });
+
+        let processed_files: Vec<ProcessedFile> = paths
+            .iter()
+            .filter_map(|path| self.processed_files.get(path))
+            .cloned()
+            .collect();
+        let mut references = resolve_references(
+            &self.configuration,
+            self.constant_resolver.as_ref(),
+            &processed_files,
+        )?;
+        self.references.append(&mut references);
+        Ok(())
+    }
+
+    fn constant_resolver_for_session(
+        &self,
+    ) -> Box<dyn ConstantResolver + Send + Sync> {
+        let processed_files: Vec<ProcessedFile> =
+            if self.configuration.experimental_parser {
+                self.processed_files.values().cloned().collect()
+            } else {
+                vec![]
+            };
+        build_constant_resolver(&self.configuration, &processed_files)
+    }
+
+    // Our own cache writes and git's bookkeeping would otherwise trigger
+    // endless re-checks.
+    fn is_ignored(&self, path: &Path) -> bool {
+        path.starts_with(&self.configuration.cache_directory)
+            || path.starts_with(self.configuration.absolute_root.join(".git"))
+    }
+}
+
+fn build_constant_resolver(
+    configuration: &Configuration,
+    processed_files: &[ProcessedFile],
+) -> Box<dyn ConstantResolver + Send + Sync> {
+    if configuration.experimental_parser {
+        get_experimental_constant_resolver(
+            &configuration.absolute_root,
+            &processed_files.to_vec(),
+            &configuration.ignored_definitions,
+        )
+    } else {
+        // The zeitwerk constant resolver doesn't look at processed files to get definitions
+        get_zeitwerk_constant_resolver(
+            &configuration.pack_set,
+            &configuration.constant_resolver_configuration(),
+        )
+    }
+}
+
+fn violation_identifiers(
+    result: &CheckAllResult,
+) -> HashSet<&ViolationIdentifier> {
+    result
+        .reportable_violations
+        .iter()
+        .chain(&result.strict_mode_violations)
+        .map(|violation| &violation.identifier)
+        .collect()
+}
+
+fn summarize(
+    previous: Option<&CheckAllResult>,
+    current: &CheckAllResult,
+) -> String {
+    let Some(previous) = previous else {
+        return String::from("Watching for changes...");
+    };
+    let previous = violation_identifiers(previous);
+    let current = violation_identifiers(current);
+    let new_count = current.difference(&previous).count();
+    let fixed_count = previous.difference(&current).count();
+    format!(
+        "Watching for changes... ({} new, {} fixed since the last run)",
+        new_count, fixed_count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let target = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &target);
+            } else {
+                fs::copy(entry.path(), &target).unwrap();
+            }
+        }
+    }
+
+    fn simple_app_session(dir: &TempDir) -> WatchSession {
+        let root = dir.path().join("simple_app");
+        copy_dir(Path::new("tests/fixtures/simple_app"), &root);
+        let mut configuration =
+            configuration::get(&root.canonicalize().unwrap()).unwrap();
+        configuration.cache_enabled = false;
+        WatchSession::new(configuration, vec![]).unwrap()
+    }
+
+    fn violation_types(result: &CheckAllResult) -> Vec<String> {
+        let mut types: Vec<String> = result
+            .reportable_violations
+            .iter()
+            .map(|v| v.identifier.violation_type.to_string())
+            .collect();
+        types.sort();
+        types
+    }
+
+    #[test]
+    fn test_rechecks_modified_file() {
+        let dir = TempDir::new().unwrap();
+        let mut session = simple_app_session(&dir);
+        assert_eq!(
+            violation_types(&session.check().unwrap()),
+            vec!["dependency", "privacy"]
+        );
+
+        let foo = session
+            .configuration
+            .absolute_root
+            .join("packs/foo/app/services/foo.rb");
+        fs::write(&foo, "module Foo\nend\n").unwrap();
+
+        assert!(session.apply_changes(&[foo]).unwrap());
+        assert!(session.check().unwrap().reportable_violations.is_empty());
+    }
+
+    #[test]
+    fn test_reloads_changed_package_yml() {
+        let dir = TempDir::new().unwrap();
+        let mut session = simple_app_session(&dir);
+
+        let package_yml = session
+            .configuration
+            .absolute_root
+            .join("packs/foo/package.yml");
+        let contents = fs::read_to_string(&package_yml).unwrap();
+        fs::write(
+            &package_yml,
+            contents.replace("- packs/baz", "- packs/bar\n- packs/baz"),
+        )
+        .unwrap();
+
+        assert!(session.apply_changes(&[package_yml]).unwrap());
+        assert_eq!(violation_types(&session.check().unwrap()), vec!["privacy"]);
+    }
+
+    #[test]
+    fn test_ignores_cache_writes() {
+        let dir = TempDir::new().unwrap();
+        let mut session = simple_app_session(&dir);
+
+        let cache_file = session.configuration.cache_directory.join("foo");
+        assert!(!session.apply_changes(&[cache_file]).unwrap());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let dir = TempDir::new().unwrap();
+        let session = simple_app_session(&dir);
+        let result = session.check().unwrap();
+        let no_violations = CheckAllResult {
+            reportable_violations: HashSet::new(),
+            stale_violations: vec![],
+            strict_mode_violations: HashSet::new(),
+            parse_errors: vec![],
+            suppressed_violations: vec![],
+            invalid_suppressions: vec![],
+        };
+
+        assert_eq!(summarize(None, &result), "Watching for changes...");
+        assert_eq!(
+            summarize(Some(&no_violations), &result),
+            "Watching for changes... (2 new, 0 fixed since the last run)"
+        );
+        assert_eq!(
+            summarize(Some(&result), &no_violations),
+            "Watching for changes... (0 new, 2 fixed since the last run)"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path};
+
+mod common;
+
+fn fixture_with_cache(name: &str) -> common::Fixture {
+    let fixture = common::Fixture::new(name);
+    let packwerk_yml = fixture.path("packwerk.yml");
+    let contents = fs::read_to_string(&packwerk_yml).unwrap();
+    fs::write(
+        &packwerk_yml,
+        contents.replace("cache: false", "cache: true"),
+    )
+    .unwrap();
+    fixture
+}
+
+fn cache_entry_names(cache_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(cache_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_cache_is_invalidated_when_custom_associations_change(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_cache("app_with_rails_relationships");
+    let packwerk_yml = fixture.path("packwerk.yml");
+    fs::write(
+        fixture.path("packs/bar/app/models/truck.rb"),
+        "class Truck < ActiveRecord::Base\n  cache_belongs_to :taco\nend\n",
+    )?;
+
+    // Without the custom association, `cache_belongs_to` is just a method call
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("truck.rb").not());
+    assert!(fixture.path("tmp/cache/packwerk/zeitwerk").exists());
+
+    let mut contents = fs::read_to_string(&packwerk_yml)?;
+    contents.push_str("\ncustom_associations:\n- cache_belongs_to\n");
+    fs::write(&packwerk_yml, contents)?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "packs/bar/app/models/truck.rb:2:2\nPrivacy violation: `::Taco`",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_cache_export_and_import() -> Result<(), Box<dyn Error>> {
+    let ci = fixture_with_cache("simple_app");
+    let laptop = fixture_with_cache("simple_app");
+    let archive = ci.path("cache.tar.gz");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(ci.root())
+        .arg("check")
+        .assert()
+        .failure();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(ci.root())
+        .arg("cache")
+        .arg("export")
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported"));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(laptop.root())
+        .arg("cache")
+        .arg("import")
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported"));
+
+    // Entries are named by root-relative paths, so the checkout at another
+    // path finds them under the same names
+    let entries = cache_entry_names(&ci.path("tmp/cache/packwerk/zeitwerk"));
+    assert!(!entries.is_empty());
+    assert_eq!(
+        entries,
+        cache_entry_names(&laptop.path("tmp/cache/packwerk/zeitwerk"))
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(laptop.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("2 violation(s) detected:"));
+    Ok(())
+}
+
+#[test]
+fn test_cache_import_rejects_archives_from_another_configuration(
+) -> Result<(), Box<dyn Error>> {
+    let ci = fixture_with_cache("simple_app");
+    let laptop = fixture_with_cache("simple_app");
+    let archive = ci.path("cache.tar.gz");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(ci.root())
+        .arg("check")
+        .assert()
+        .failure();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(ci.root())
+        .arg("cache")
+        .arg("export")
+        .arg(&archive)
+        .assert()
+        .success();
+
+    let mut packwerk_yml = fs::read_to_string(laptop.path("packwerk.yml"))?;
+    packwerk_yml.push_str("\ncustom_associations:\n- cache_belongs_to\n");
+    fs::write(laptop.path("packwerk.yml"), packwerk_yml)?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(laptop.root())
+        .arg("cache")
+        .arg("import")
+        .arg(&archive)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "was built with a different pks version or configuration",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_cache_stats_verify_and_prune() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_cache("simple_app");
+    for _ in 0..2 {
+        cargo_bin_cmd!("pks")
+            .arg("--project-root")
+            .arg(fixture.root())
+            .arg("check")
+            .assert()
+            .failure();
+    }
+
+    let entries =
+        cache_entry_names(&fixture.path("tmp/cache/packwerk/zeitwerk"))
+            .into_iter()
+            .filter(|name| name != "last_run.json")
+            .count();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("cache")
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "zeitwerk: {} entries",
+            entries
+        )))
+        .stdout(predicate::str::contains(format!(
+            "Last run: {} hits, 0 misses (100.0% hit rate)",
+            entries
+        )))
+        .stdout(predicate::str::contains(format!(
+            "resolved_references: {} entries",
+            entries
+        )));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("cache")
+        .arg("verify")
+        .assert()
+        .success()
+        .stdout(format!(
+            "Verified {} cache entries, none are corrupt\n",
+            // Parse results and resolved references
+            entries * 2
+        ));
+
+    // Removes both its parse result and its resolved references
+    fs::remove_file(fixture.path("packs/bar/app/services/bar.rb"))?;
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("cache")
+        .arg("prune")
+        .assert()
+        .success()
+        .stdout(
+            "Removed 2 cache entries for files that are no longer included\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn test_cache_verify_reports_corrupt_entries() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_cache("simple_app");
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure();
+
+    let cache_dir = fixture.path("tmp/cache/packwerk/zeitwerk");
+    let corrupt = cache_dir.join(&cache_entry_names(&cache_dir)[0]);
+    fs::write(&corrupt, "{\"file_contents_digest\":")?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("cache")
+        .arg("verify")
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(corrupt.display().to_string()))
+        .stderr(predicate::str::contains("cache entries are corrupt"));
+    Ok(())
+}
+
+#[test]
+fn test_cached_references_are_resolved_again_when_definitions_move(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_cache("simple_app");
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("`::Bar` belongs to `packs/bar`"));
+
+    // packs/foo/app/services/foo.rb is unchanged, but `::Bar` now lives in
+    // packs/baz, which packs/foo depends on
+    fs::rename(
+        fixture.path("packs/bar/app/services/bar.rb"),
+        fixture.path("packs/baz/app/services/bar.rb"),
+    )?;
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .stdout(predicate::str::contains("packs/bar").not());
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(root)
+        .args(["-c", "user.name=pks", "-c", "user.email=pks@example.com"])
+        .args(args)
+        .status()
+        .expect("could not run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+// A copy of simple_app committed to a fresh repository. Its packs/foo
+// already references ::Bar without depending on packs/bar.
+fn committed_fixture() -> common::Fixture {
+    let fixture = common::Fixture::new("simple_app");
+    git(fixture.root(), &["init", "--quiet"]);
+    git(fixture.root(), &["add", "--all"]);
+    git(fixture.root(), &["commit", "--quiet", "-m", "base"]);
+    fixture
+}
+
+#[test]
+fn test_check_since_without_changes() -> Result<(), Box<dyn Error>> {
+    let fixture = committed_fixture();
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("--since")
+        .arg("HEAD")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_reports_only_new_violations() -> Result<(), Box<dyn Error>>
+{
+    let fixture = committed_fixture();
+    // Another reference to a constant foo.rb already used is not new
+    let foo = fixture.path("packs/foo/app/services/foo.rb");
+    let contents = fs::read_to_string(&foo)?;
+    fs::write(&foo, contents.replace("::Bar\n", "::Bar\n    ::Bar\n"))?;
+    fs::write(
+        fixture.path("packs/foo/app/services/foo/qux.rb"),
+        "module Foo\n  class Qux\n    ::Bar\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("--since")
+        .arg("HEAD")
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "packs/foo/app/services/foo/qux.rb:3:4\nDependency violation: `::Bar`",
+        ))
+        .stdout(predicate::str::contains("packs/foo/app/services/foo.rb").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_unknown_ref() -> Result<(), Box<dyn Error>> {
+    let fixture = committed_fixture();
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("--since")
+        .arg("no-such-branch")
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "`no-such-branch` is not a git commit",
+        ));
+
+    Ok(())
+}
@@ -648,6 +648,45 @@ fn test_check_with_json_output_format_empty() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_check_with_json_output_format_suppressed_violations(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    fs::write(
+        fixture.path("packs/foo/app/services/foo.rb"),
+        "module Foo\n  def calls_bar\n    # pks:disable dependency -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    )?;
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("-o")
+        .arg("json")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let json_output: serde_json::Value =
+        serde_json::from_slice(&output).expect("Output should be valid JSON");
+
+    validate_check_output_schema(&json_output);
+
+    assert_eq!(json_output["summary"]["violation_count"], 1);
+    assert_eq!(json_output["summary"]["suppressed_violation_count"], 1);
+    assert_eq!(json_output["violations"][0]["violation_type"], "privacy");
+    let suppressed = &json_output["suppressed_violations"][0];
+    assert_eq!(suppressed["violation_type"], "dependency");
+    assert_eq!(suppressed["file"], "packs/foo/app/services/foo.rb");
+    assert_eq!(suppressed["line"], 4);
+    assert_eq!(suppressed["constant_name"], "::Bar");
+    assert_eq!(suppressed["reason"], "Migrating to packs/bar");
+
+    Ok(())
+}
+
 #[test]
 fn test_check_with_nonexistent_project_root() -> Result<(), Box<dyn Error>> {
     // Exit code 2 for internal errors (non-existent project root)
@@ -735,3 +774,206 @@ fn test_check_with_color_never() -> Result<(), Box<dyn Error>> {
     common::teardown();
     Ok(())
 }
+
+#[test]
+fn test_check_with_sarif_output_format_violations() -> Result<(), Box<dyn Error>>
+{
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("-o")
+        .arg("sarif")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let sarif: serde_json::Value =
+        serde_json::from_slice(&output).expect("Output should be valid JSON");
+
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "pks");
+
+    let rule_ids: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        rule_ids,
+        vec![
+            "dependency",
+            "folder_privacy",
+            "layer",
+            "privacy",
+            "visibility"
+        ]
+    );
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    let dependency_result = &results[0];
+    assert_eq!(dependency_result["ruleId"], "dependency");
+    assert_eq!(dependency_result["ruleIndex"], 0);
+    assert_eq!(dependency_result["level"], "error");
+    assert_eq!(
+        dependency_result["message"]["text"],
+        "Dependency violation: `::Bar` belongs to `packs/bar`, but `packs/foo/package.yml` does not specify a dependency on `packs/bar`."
+    );
+    let physical_location =
+        &dependency_result["locations"][0]["physicalLocation"];
+    assert_eq!(
+        physical_location["artifactLocation"]["uri"],
+        "packs/foo/app/services/foo.rb"
+    );
+    assert_eq!(physical_location["region"]["startLine"], 3);
+    assert_eq!(physical_location["region"]["startColumn"], 5);
+    assert_eq!(dependency_result["properties"]["constantName"], "::Bar");
+    assert_eq!(
+        dependency_result["properties"]["referencingPackName"],
+        "packs/foo"
+    );
+    assert_eq!(
+        dependency_result["properties"]["definingPackName"],
+        "packs/bar"
+    );
+
+    let privacy_result = &results[1];
+    assert_eq!(privacy_result["ruleId"], "privacy");
+    assert_eq!(privacy_result["ruleIndex"], 3);
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_with_sarif_output_format_empty() -> Result<(), Box<dyn Error>> {
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/contains_package_todo")
+        .arg("check")
+        .arg("-o")
+        .arg("sarif")
+        .assert()
+        .code(0)
+        .get_output()
+        .stdout
+        .clone();
+
+    let sarif: serde_json::Value =
+        serde_json::from_slice(&output).expect("Output should be valid JSON");
+
+    assert!(sarif["runs"][0]["results"].as_array().unwrap().is_empty());
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_with_junit_output_format_violations() -> Result<(), Box<dyn Error>>
+{
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("-o")
+        .arg("junit")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let xml = String::from_utf8(output)?;
+    assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(
+        xml.contains(r#"<testsuites name="pks check" tests="2" failures="2">"#)
+    );
+    assert!(
+        xml.contains(r#"<testsuite name="packs/foo" tests="2" failures="2">"#)
+    );
+    assert_eq!(xml.matches("<testcase ").count(), 2);
+    assert!(xml.contains(r#"<failure type="dependency" message="Dependency violation: `::Bar` belongs to `packs/bar`, but `packs/foo/package.yml` does not specify a dependency on `packs/bar`.">"#));
+    assert!(xml.contains(r#"<failure type="privacy" message="Privacy violation: `::Bar` is private to `packs/bar`, but referenced from `packs/foo`">"#));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_with_junit_output_format_empty() -> Result<(), Box<dyn Error>> {
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/contains_package_todo")
+        .arg("check")
+        .arg("-o")
+        .arg("junit")
+        .assert()
+        .code(0)
+        .get_output()
+        .stdout
+        .clone();
+
+    let xml = String::from_utf8(output)?;
+    assert!(
+        xml.contains(r#"<testsuites name="pks check" tests="0" failures="0">"#)
+    );
+    assert!(!xml.contains("<testcase"));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_with_checkstyle_output_format_violations(
+) -> Result<(), Box<dyn Error>> {
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("-o")
+        .arg("checkstyle")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let xml = String::from_utf8(output)?;
+    assert!(xml.contains(r#"<checkstyle version="4.3">"#));
+    assert_eq!(xml.matches("<file ").count(), 1);
+    assert!(xml.contains(r#"<file name="packs/foo/app/services/foo.rb">"#));
+    assert!(xml.contains(r#"<error line="3" column="5" severity="error" message="Dependency violation: `::Bar` belongs to `packs/bar`, but `packs/foo/package.yml` does not specify a dependency on `packs/bar`." source="pks.dependency"/>"#));
+    assert!(xml.contains(r#"<error line="3" column="5" severity="error" message="Privacy violation: `::Bar` is private to `packs/bar`, but referenced from `packs/foo`" source="pks.privacy"/>"#));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_with_checkstyle_output_format_empty() -> Result<(), Box<dyn Error>>
+{
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/contains_package_todo")
+        .arg("check")
+        .arg("-o")
+        .arg("checkstyle")
+        .assert()
+        .code(0)
+        .get_output()
+        .stdout
+        .clone();
+
+    let xml = String::from_utf8(output)?;
+    assert!(xml.contains(r#"<checkstyle version="4.3">"#));
+    assert!(!xml.contains("<file"));
+
+    common::teardown();
+    Ok(())
+}
@@ -40,3 +40,122 @@ fn list_pack_dependencies_with_implicit_dependencies(
     common::teardown();
     Ok(())
 }
+
+// packs/bar -> packs/foo -> packs/baz
+fn fixture_with_dependency_chain() -> common::Fixture {
+    let fixture = common::Fixture::new("simple_app");
+    std::fs::write(
+        fixture.path("packs/bar/package.yml"),
+        "enforce_privacy: true\ndependencies:\n- packs/foo\n",
+    )
+    .unwrap();
+    fixture
+}
+
+#[test]
+fn test_list_pack_dependencies_transitively() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_dependency_chain();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-pack-dependencies")
+        .arg("packs/baz")
+        .arg("--transitive")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Packs that transitively depend on packs/baz\n\n\
+             - packs/foo\n  - packs/bar\n\n2 pack(s)",
+        ));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-pack-dependencies")
+        .arg("packs/baz")
+        .arg("--depth")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- packs/foo\n\n1 pack(s)"));
+    Ok(())
+}
+
+#[test]
+fn test_list_pack_dependencies_in_reverse() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_dependency_chain();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-pack-dependencies")
+        .arg("packs/bar")
+        .arg("--reverse")
+        .arg("--transitive")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Packs that packs/bar transitively depends on\n\n\
+             - packs/foo\n  - packs/baz\n\n2 pack(s)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_list_pack_dependencies_of_diamond_with_depth(
+) -> Result<(), Box<dyn Error>> {
+    // packs/a -> packs/b -> packs/c -> packs/d, and packs/a -> packs/c
+    let fixture = common::Fixture::new("simple_app");
+    for (pack, package_yml) in [
+        ("packs/a", "dependencies:\n- packs/b\n- packs/c\n"),
+        ("packs/b", "dependencies:\n- packs/c\n"),
+        ("packs/c", "dependencies:\n- packs/d\n"),
+        ("packs/d", ""),
+    ] {
+        std::fs::create_dir_all(fixture.path(pack))?;
+        std::fs::write(fixture.path(pack).join("package.yml"), package_yml)?;
+    }
+
+    // packs/c is first reached at the depth limit, which must not hide
+    // packs/d where it is reached again
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-pack-dependencies")
+        .arg("packs/a")
+        .arg("--reverse")
+        .arg("--depth")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "- packs/b\n  - packs/c\n- packs/c\n  - packs/d\n\n3 pack(s)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_path() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_dependency_chain();
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("path")
+        .arg("packs/bar")
+        .arg("packs/baz")
+        .assert()
+        .success()
+        .stdout("packs/bar -> packs/foo -> packs/baz\n");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("path")
+        .arg("packs/baz")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(
+            "packs/baz does not depend on packs/bar, directly or transitively\n",
+        );
+    Ok(())
+}
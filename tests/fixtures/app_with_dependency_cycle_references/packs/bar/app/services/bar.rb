@@ -0,0 +1,6 @@
+module Bar
+  def self.call
+    Foo
+    Baz
+  end
+end
@@ -0,0 +1,119 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+
+mod common;
+
+#[test]
+fn test_graph_declared_dependencies_as_dot() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("graph")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("digraph packs {"))
+        .stdout(predicate::str::contains(r#""packs/foo" -> "packs/baz";"#))
+        .stdout(
+            predicate::str::contains(r#""packs/foo" -> "packs/bar""#).not(),
+        );
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_graph_with_unused_dependency() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_missing_dependency")
+        .arg("graph")
+        .arg("--references")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""packs/foo" -> "packs/bar" [style=dashed, color=gray, label="0"];"#,
+        ));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_graph_colors_packs_by_layer() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/layer_violations")
+        .arg("graph")
+        .arg("--references")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r##""packs/feature_flags" [style=filled, fillcolor="#ffffb3", tooltip="utilities"];"##,
+        ))
+        .stdout(predicate::str::contains(
+            r##""packs/payments" [style=filled, fillcolor="#8dd3c7", tooltip="product"];"##,
+        ))
+        .stdout(predicate::str::contains(
+            r#""packs/feature_flags" -> "packs/payments" [color=red, label="1"];"#,
+        ));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_graph_with_undeclared_references_as_json() -> Result<(), Box<dyn Error>>
+{
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("graph")
+        .arg("--references")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let graph: serde_json::Value = serde_json::from_slice(&output)?;
+    let nodes: Vec<&str> = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(nodes, vec![".", "packs/bar", "packs/baz", "packs/foo"]);
+
+    let edges = graph["edges"].as_array().unwrap();
+    let undeclared = edges
+        .iter()
+        .find(|edge| edge["to"] == "packs/bar")
+        .expect("packs/foo references packs/bar");
+    assert_eq!(undeclared["from"], "packs/foo");
+    assert_eq!(undeclared["kind"], "used_but_undeclared");
+    assert_eq!(undeclared["references"], 1);
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_graph_as_mermaid() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("graph")
+        .arg("--format")
+        .arg("mermaid")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("graph LR"))
+        .stdout(predicate::str::contains(r#"n3["packs/foo"]"#))
+        .stdout(predicate::str::contains("n3 --> n2"));
+
+    common::teardown();
+    Ok(())
+}
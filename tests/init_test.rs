@@ -0,0 +1,76 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_init() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("packs/foo/app/services"))?;
+    fs::write(root.join("packs/foo/package.yml"), "")?;
+    fs::write(
+        root.join("packs/foo/app/services/foo.rb"),
+        "module Foo\nend\n",
+    )?;
+    fs::write(root.join("Gemfile"), "gem \"rspec\"\n")?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(root)
+        .arg("init")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Successfully created `packwerk.yml`!",
+        ))
+        .stdout(predicate::str::contains(
+            "Successfully created `package.yml`!",
+        ));
+
+    let packwerk_yml = fs::read_to_string(root.join("packwerk.yml"))?;
+    assert!(packwerk_yml.contains("package_paths:\n- \"packs/*\"\n"));
+    assert!(
+        packwerk_yml.contains("include:\n- \"**/*.rb\"\n- \"**/*.rake\"\n\n")
+    );
+    assert!(packwerk_yml.contains("cache: true\n"));
+    assert!(packwerk_yml.contains("# layers:\n"));
+    assert_eq!(
+        fs::read_to_string(root.join("package.yml"))?,
+        "enforce_dependencies: true\n"
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(root)
+        .arg("list-packs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("packs/foo/package.yml"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_already_initialized() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    let packwerk_yml = fs::read_to_string(fixture.path("packwerk.yml"))?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("init")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("`packwerk.yml` already exists!"))
+        .stdout(predicate::str::contains("`package.yml` already exists!"));
+
+    assert_eq!(
+        fs::read_to_string(fixture.path("packwerk.yml"))?,
+        packwerk_yml
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use packs::packs;
+
+mod common;
+
+#[test]
+fn test_check_all() -> Result<(), Box<dyn Error>> {
+    let configuration =
+        packs::configuration(PathBuf::from("tests/fixtures/simple_app"))?;
+    let result = packs::check_all(&configuration, vec![])?;
+
+    assert!(result.has_violations());
+    let mut violation_types: Vec<String> = result
+        .reportable_violations
+        .iter()
+        .map(|v| v.identifier.violation_type.to_string())
+        .collect();
+    violation_types.sort();
+    assert_eq!(violation_types, vec!["dependency", "privacy"]);
+    assert!(result.reportable_violations.iter().any(|v| v
+        .identifier
+        .violation_type
+        == packs::CheckerType::Privacy
+        && v.identifier.constant_name == "::Bar"));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_all_references() -> Result<(), Box<dyn Error>> {
+    let configuration =
+        packs::configuration(PathBuf::from("tests/fixtures/simple_app"))?;
+    let references = packs::all_references(
+        &configuration,
+        vec![String::from("packs/foo/app/services/foo.rb")],
+    )?;
+
+    let reference = references
+        .iter()
+        .find(|r| r.constant_name == "::Bar")
+        .expect("packs/foo references ::Bar");
+    assert_eq!(reference.referencing_pack_name, "packs/foo");
+    assert_eq!(reference.defining_pack_name.as_deref(), Some("packs/bar"));
+    assert_eq!(reference.source_location.line, 3);
+    assert!(references.iter().all(
+        |r| r.relative_referencing_file == "packs/foo/app/services/foo.rb"
+    ));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_pack_set_lookups() -> Result<(), Box<dyn Error>> {
+    let configuration =
+        packs::configuration(PathBuf::from("tests/fixtures/simple_app"))?;
+    let pack_set = &configuration.pack_set;
+
+    let foo = pack_set.for_pack("packs/foo")?;
+    assert!(foo.dependencies.contains("packs/baz"));
+    assert!(pack_set.for_pack("packs/nope").is_err());
+
+    let file = configuration
+        .absolute_root
+        .join("packs/bar/app/services/bar.rb");
+    let owner = pack_set.for_file(&file)?.expect("file belongs to a pack");
+    assert_eq!(owner.name, "packs/bar");
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_all_result_types() -> Result<(), Box<dyn Error>> {
+    let configuration =
+        packs::configuration(PathBuf::from("tests/fixtures/simple_app"))?;
+    let result = packs::check_all(&configuration, vec![])?;
+
+    let parse_errors: &[packs::ParseError] = &result.parse_errors;
+    let suppressed_violations: &[packs::SuppressedViolation] =
+        &result.suppressed_violations;
+    let invalid_suppressions: &[packs::ParseError] =
+        &result.invalid_suppressions;
+    assert!(parse_errors.is_empty());
+    assert!(suppressed_violations.is_empty());
+    assert!(invalid_suppressions.is_empty());
+
+    let references = packs::all_references(&configuration, vec![])?;
+    let suppressions: Vec<&packs::Suppression> = references
+        .iter()
+        .flat_map(|reference| &reference.suppressions)
+        .collect();
+    assert!(suppressions.is_empty());
+
+    common::teardown();
+    Ok(())
+}
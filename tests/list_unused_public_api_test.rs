@@ -0,0 +1,94 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_list_unused_public_api() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("app_with_missing_dependencies");
+    fs::write(
+        fixture.path("packs/bar/app/public/bar/unused.rb"),
+        "module Bar\n  module Unused\n    def self.call\n    end\n  end\nend\n",
+    )?;
+    // Only referenced from within packs/bar, so it could be private
+    fs::write(
+        fixture.path("packs/bar/app/public/bar/internal.rb"),
+        "module Bar\n  module Internal\n    def self.call\n    end\n  end\nend\n",
+    )?;
+    fs::create_dir_all(fixture.path("packs/bar/app/services/bar"))?;
+    fs::write(
+        fixture.path("packs/bar/app/services/bar/user.rb"),
+        "module Bar\n  module User\n    def self.call\n      Internal.call\n    end\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-unused-public-api")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "packs/bar
+- ::Bar::Internal (packs/bar/app/public/bar/internal.rb)
+- ::Bar::Unused (packs/bar/app/public/bar/unused.rb)
+
+2 public constant(s) are not referenced from any other pack
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_list_unused_public_api_when_all_used() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("app_with_missing_dependencies");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("list-unused-public-api")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No unused public constants!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_list_unused_public_api_with_same_constant_in_two_packs(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("app_with_missing_dependencies");
+    // Both packs define ::Shared, and only packs/foo references it, so only
+    // the definition in packs/bar is used by another pack
+    fs::write(
+        fixture.path("packs/bar/app/public/shared.rb"),
+        "module Shared\n  def self.call\n  end\nend\n",
+    )?;
+    fs::create_dir_all(fixture.path("packs/foo/app/public"))?;
+    fs::write(
+        fixture.path("packs/foo/app/public/shared.rb"),
+        "module Shared\n  def self.call\n  end\nend\n",
+    )?;
+    fs::write(
+        fixture.path("packs/foo/app/services/foo/no_bar.rb"),
+        "module Foo\n  module NoBar\n    def self.call\n      Shared.call\n    end\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("--experimental-parser")
+        .arg("list-unused-public-api")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "packs/foo\n- ::Shared (packs/foo/app/public/shared.rb)\n",
+        ))
+        .stdout(
+            predicate::str::contains("packs/bar/app/public/shared.rb").not(),
+        );
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::{json, Value};
+use std::error::Error;
+
+mod common;
+
+fn frame(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+fn session(messages: Vec<Value>) -> String {
+    let mut input: String = messages.into_iter().map(frame).collect();
+    input.push_str(&frame(
+        json!({"jsonrpc": "2.0", "id": 99, "method": "shutdown"}),
+    ));
+    input.push_str(&frame(json!({"jsonrpc": "2.0", "method": "exit"})));
+    input
+}
+
+// Splits the server's output back into JSON messages.
+fn messages(output: &[u8]) -> Vec<Value> {
+    let output = String::from_utf8_lossy(output);
+    output
+        .split("Content-Length: ")
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| {
+            let (_, body) = chunk.split_once("\r\n\r\n").unwrap();
+            serde_json::from_str(body).unwrap()
+        })
+        .collect()
+}
+
+fn published_diagnostics(messages: &[Value]) -> Vec<&Value> {
+    messages
+        .iter()
+        .filter(|m| m["method"] == "textDocument/publishDiagnostics")
+        .collect()
+}
+
+fn initialize() -> Vec<Value> {
+    vec![
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+    ]
+}
+
+#[test]
+fn test_lsp_publishes_violations_for_open_buffer() -> Result<(), Box<dyn Error>>
+{
+    let fixture = common::Fixture::new("simple_app");
+    let file = fixture
+        .path("packs/foo/app/services/foo.rb")
+        .canonicalize()?;
+    let uri = format!("file://{}", file.display());
+    let text = std::fs::read_to_string(&file)?;
+
+    let mut input = initialize();
+    input.push(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {"textDocument": {"uri": uri, "languageId": "ruby", "version": 1, "text": text}}
+    }));
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("lsp")
+        .write_stdin(session(input))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let messages = messages(&output);
+    let published = published_diagnostics(&messages);
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0]["params"]["uri"], uri);
+
+    let diagnostics = published[0]["params"]["diagnostics"].as_array().unwrap();
+    let codes: Vec<&str> = diagnostics
+        .iter()
+        .map(|d| d["code"].as_str().unwrap())
+        .collect();
+    assert_eq!(codes, vec!["dependency", "privacy"]);
+    for diagnostic in diagnostics {
+        assert_eq!(diagnostic["source"], "pks");
+        assert_eq!(diagnostic["range"]["start"]["line"], 2);
+        assert_eq!(diagnostic["range"]["start"]["character"], 4);
+        assert!(diagnostic["message"].as_str().unwrap().contains("`::Bar`"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_lsp_checks_unsaved_changes() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    let file = fixture
+        .path("packs/foo/app/services/foo.rb")
+        .canonicalize()?;
+    let uri = format!("file://{}", file.display());
+    let text = std::fs::read_to_string(&file)?;
+
+    let mut input = initialize();
+    input.push(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {"textDocument": {"uri": uri, "languageId": "ruby", "version": 1, "text": text}}
+    }));
+    input.push(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": {"uri": uri, "version": 2},
+            "contentChanges": [{"text": "module Foo\nend\n"}]
+        }
+    }));
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("lsp")
+        .write_stdin(session(input))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let messages = messages(&output);
+    let published = published_diagnostics(&messages);
+    assert_eq!(published.len(), 2);
+    assert_eq!(
+        published[0]["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(published[1]["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_lsp_ignores_files_outside_of_packs() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    let uri = "file:///not/a/pack/file.rb";
+
+    let mut input = initialize();
+    input.push(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {"textDocument": {"uri": uri, "languageId": "ruby", "version": 1, "text": "::Bar\n"}}
+    }));
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("lsp")
+        .write_stdin(session(input))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let messages = messages(&output);
+    let published = published_diagnostics(&messages);
+    assert_eq!(published.len(), 1);
+    assert!(published[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    Ok(())
+}
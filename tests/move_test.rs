@@ -0,0 +1,118 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_move_resolves_recorded_violation() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("move")
+        .arg("packs/foo/app/services/other_foo.rb")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Moved `packs/foo/app/services/other_foo.rb` to `packs/bar/app/services/other_foo.rb`",
+        ))
+        .stdout(predicate::str::contains(
+            "1 recorded violation(s) no longer occur",
+        ))
+        .stdout(predicate::str::contains("No new violations."));
+
+    assert!(!fixture.path("packs/foo/app/services/other_foo.rb").exists());
+    assert!(fixture.path("packs/bar/app/services/other_foo.rb").exists());
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+    assert!(package_todo.contains("- packs/foo/app/services/foo.rb"));
+    assert!(!package_todo.contains("other_foo.rb"));
+
+    Ok(())
+}
+
+#[test]
+fn test_move_rewrites_package_todo_for_moved_constant(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("move")
+        .arg("packs/bar/app")
+        .arg(".")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Moved `packs/bar/app/services/bar.rb` to `app/services/bar.rb`",
+        ))
+        .stdout(predicate::str::contains("No new violations."));
+
+    assert!(!fixture.path("packs/bar/app").exists());
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+    assert!(package_todo.contains("\".\":\n  \"::Bar\":"));
+    assert!(!package_todo.contains("packs/bar"));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_move_reports_new_violations() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    fs::create_dir_all(fixture.path("app/services"))?;
+    fs::write(
+        fixture.path("app/services/qux.rb"),
+        "module Qux\n  ::Bar\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("move")
+        .arg("app/services/qux.rb")
+        .arg("packs/foo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 new violation(s) after the move:\npacks/foo/app/services/qux.rb:2:2\nDependency violation: `::Bar`",
+        ));
+
+    // New violations are reported, not recorded
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+    assert!(!package_todo.contains("qux.rb"));
+
+    Ok(())
+}
+
+#[test]
+fn test_move_to_same_pack() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("move")
+        .arg("packs/foo/app/services/foo.rb")
+        .arg("packs/foo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`packs/foo/app/services/foo.rb` already belongs to `packs/foo`",
+        ));
+
+    Ok(())
+}
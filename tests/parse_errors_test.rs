@@ -0,0 +1,154 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+// contains_package_todo has no unrecorded violations; the broken file hides
+// a new dependency violation on ::Bar.
+fn fixture_with_broken_file() -> common::Fixture {
+    let fixture = common::Fixture::new("contains_package_todo");
+    fs::write(
+        fixture.path("packs/foo/app/services/broken_foo.rb"),
+        "module Foo\n  def calls_bar\n    Bar\n  end\n\n  def broken(\nend\n",
+    )
+    .unwrap();
+    fixture
+}
+
+#[test]
+fn test_check_reports_parse_errors() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_broken_file();
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "parse error(s) detected, violations in these files may be missing:",
+        ))
+        .stdout(predicate::str::contains(
+            "packs/foo/app/services/broken_foo.rb:7:0\nunexpected kEND",
+        ))
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_fail_on_parse_errors() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_broken_file();
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("--fail-on-parse-errors")
+        .arg("-o")
+        .arg("json")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output)?;
+
+    assert_eq!(json["summary"]["violation_count"], 0);
+    assert_eq!(json["summary"]["success"], false);
+    let parse_errors = json["parse_errors"].as_array().unwrap();
+    assert!(!parse_errors.is_empty());
+    assert!(parse_errors.iter().all(|parse_error| {
+        parse_error["file"] == "packs/foo/app/services/broken_foo.rb"
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn test_fail_on_parse_errors_from_packwerk_yml() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_broken_file();
+    let packwerk_yml = fixture.path("packwerk.yml");
+    let contents = fs::read_to_string(&packwerk_yml)?;
+    fs::write(&packwerk_yml, contents + "\nfail_on_parse_errors: true\n")?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("unexpected kEND"))
+        .stdout(predicate::str::contains("No violations detected!").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_errors_survive_the_cache() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_broken_file();
+    let packwerk_yml = fixture.path("packwerk.yml");
+    let contents = fs::read_to_string(&packwerk_yml)?;
+    fs::write(
+        &packwerk_yml,
+        contents.replace("cache: false", "cache: true"),
+    )?;
+
+    for _ in 0..2 {
+        cargo_bin_cmd!("pks")
+            .arg("--project-root")
+            .arg(fixture.root())
+            .arg("check")
+            .arg("--fail-on-parse-errors")
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("unexpected kEND"));
+        assert!(fixture.path("tmp/cache/packwerk").exists());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_check_reports_parse_errors_as_csv() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_broken_file();
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("-o")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "parse_error,false,packs/foo/app/services/broken_foo.rb,,,,unexpected kEND,7,0\n",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_erb_parse_errors_are_reported_on_the_first_line(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    fs::create_dir_all(fixture.path("packs/foo/app/views"))?;
+    fs::write(
+        fixture.path("packs/foo/app/views/broken.html.erb"),
+        "<div>\n  <%= link_to( %>\n</div>\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "packs/foo/app/views/broken.html.erb:1:0\n",
+        ));
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::error::Error;
+
+mod common;
+
+#[test]
+fn test_references_to_pack() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--to-pack")
+        .arg("packs/bar")
+        .arg("--from-pack")
+        .arg("packs/foo")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "packs/foo/app/services/foo.rb:3:4 ::Bar (packs/foo -> packs/bar) [dependency violation, privacy violation]
+
+1 reference(s), 1 with violations
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_references_to_constant_referenced_more_than_once(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    std::fs::write(
+        fixture.path("packs/foo/app/services/foo.rb"),
+        "module Foo\n  def calls_bar\n    ::Bar\n    ::Bar\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--to-pack")
+        .arg("packs/bar")
+        .arg("--from-pack")
+        .arg("packs/foo")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "packs/foo/app/services/foo.rb:3:4 ::Bar (packs/foo -> packs/bar) [dependency violation, privacy violation]
+packs/foo/app/services/foo.rb:4:4 ::Bar (packs/foo -> packs/bar) [dependency violation, privacy violation]
+
+2 reference(s), 2 with violations
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_references_recorded_violations() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--constant")
+        .arg("Bar")
+        .arg("--file")
+        .arg("packs/foo/app/services/other_foo.rb")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "packs/foo/app/services/other_foo.rb:3:4 ::Bar (packs/foo -> packs/bar) [dependency violation (recorded)]
+
+1 reference(s), 1 with violations
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_references_json() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--from-pack")
+        .arg("packs/foo")
+        .arg("--constant")
+        .arg("::Bar")
+        .arg("--format")
+        .arg("json")
+        .output()?;
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let references = json["references"].as_array().unwrap();
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0]["file"], "packs/foo/app/services/foo.rb");
+    assert_eq!(references[0]["line"], 3);
+    assert_eq!(references[0]["defining_pack_name"], "packs/bar");
+    assert_eq!(
+        references[0]["violations"][0]["violation_type"],
+        "dependency"
+    );
+    assert_eq!(references[0]["violations"][0]["recorded"], false);
+
+    Ok(())
+}
+
+#[test]
+fn test_references_json_recorded_strict_violations(
+) -> Result<(), Box<dyn Error>> {
+    // packs/foo records the violations that strict mode now forbids
+    let fixture = common::Fixture::new("uses_strict_mode");
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--from-pack")
+        .arg("packs/foo")
+        .arg("--to-pack")
+        .arg("packs/bar")
+        .arg("--format")
+        .arg("json")
+        .output()?;
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let violations = json["references"][0]["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 2);
+    for violation in violations {
+        assert_eq!(violation["strict"], true);
+        assert_eq!(violation["recorded"], true);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_references_unknown_pack() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("references")
+        .arg("--to-pack")
+        .arg("packs/nope")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No pack found 'packs/nope'"));
+
+    Ok(())
+}
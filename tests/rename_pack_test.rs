@@ -0,0 +1,153 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_rename_pack_updates_references() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    fs::write(
+        fixture.path("packs/foo/package.yml"),
+        "enforce_dependencies: true\nvisible_to:\n- packs/bar\nenforcement_globs_ignore:\n- enforcements:\n  - privacy\n  ignores:\n  - packs/bar/app/models/**\n  reason: Legacy\n",
+    )?;
+    fs::write(
+        fixture.path("package.yml"),
+        "ignored_dependencies:\n- packs/bar\n",
+    )?;
+    let packwerk_yml = fs::read_to_string(fixture.path("packwerk.yml"))?;
+    fs::write(
+        fixture.path("packwerk.yml"),
+        packwerk_yml
+            + "\nautoload_roots:\n  packs/bar/app/services: \"::Object\"\n"
+            + "\nexclude:\n- packs/bar/tmp/**/*\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("rename-pack")
+        .arg("packs/bar")
+        .arg("components/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renamed `packs/bar` to `components/bar`",
+        ));
+
+    assert!(!fixture.path("packs/bar").exists());
+    assert!(fixture.path("components/bar/app/services/bar.rb").exists());
+    assert_eq!(
+        fs::read_to_string(fixture.path("packs/foo/package.yml"))?,
+        "enforce_dependencies: true\nvisible_to:\n- components/bar\nenforcement_globs_ignore:\n- enforcements:\n  - privacy\n  ignores:\n  - components/bar/app/models/**\n  reason: Legacy\n"
+    );
+    assert_eq!(
+        fs::read_to_string(fixture.path("package.yml"))?,
+        "ignored_dependencies:\n- components/bar\n"
+    );
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+    assert!(package_todo.contains("\ncomponents/bar:\n  \"::Bar\":"));
+    assert!(fs::read_to_string(fixture.path("packwerk.yml"))?
+        .contains("  components/bar/app/services: \"::Object\"\n"));
+    assert!(fs::read_to_string(fixture.path("packwerk.yml"))?
+        .contains("\nexclude:\n- components/bar/tmp/**/*\n"));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_pack_moves_its_package_todo() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("rename-pack")
+        .arg("packs/foo")
+        .arg("packs/foo_v2")
+        .assert()
+        .success();
+
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo_v2/package_todo.yml"))?;
+    assert!(package_todo.contains("'packs/foo_v2' package"));
+    assert!(package_todo.contains("- packs/foo_v2/app/services/foo.rb"));
+    assert!(package_todo.contains("- packs/foo_v2/app/services/other_foo.rb"));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_pack_to_existing_pack() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("rename-pack")
+        .arg("packs/foo")
+        .arg("packs/bar")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`packs/bar` already exists"));
+
+    assert!(fixture.path("packs/foo/package.yml").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_pack_outside_of_package_paths() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    let packwerk_yml = fs::read_to_string(fixture.path("packwerk.yml"))?;
+    fs::write(
+        fixture.path("packwerk.yml"),
+        packwerk_yml + "\npackage_paths:\n- packs/*\n",
+    )?;
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("rename-pack")
+        .arg("packs/bar")
+        .arg("components/bar")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`components/bar` does not match any of the `package_paths` in packwerk.yml",
+        ));
+
+    assert!(fixture.path("packs/bar/package.yml").exists());
+    assert!(!fixture.path("components").exists());
+    assert_eq!(
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?,
+        package_todo
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_stats_table() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/contains_package_todo")
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "2 recorded, 0 new, 0 stale and 0 strict mode violation(s)",
+        ))
+        .stdout(predicate::str::contains("dependency  2         0\n"))
+        .stdout(predicate::str::contains(
+            "packs/foo  -      2         0    -       0/2 (0%)\n",
+        ));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_stats_counts_repeated_references_once() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    fs::write(
+        fixture.path("packs/foo/app/services/foo.rb"),
+        "module Foo\n  def calls_bar\n    ::Bar\n    ::Bar\n    ::Bar\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "0 recorded, 2 new, 0 stale and 0 strict mode violation(s)",
+        ));
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("update")
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "2 recorded, 0 new, 0 stale and 0 strict mode violation(s)",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_json_counts_new_violations() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    fs::write(
+        fixture.path("packs/foo/app/services/third_foo.rb"),
+        "module Foo\n  def calls_bar\n    Bar\n  end\nend\n",
+    )?;
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("stats")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stats: serde_json::Value = serde_json::from_slice(&output)?;
+
+    assert_eq!(stats["totals"]["recorded"], 2);
+    assert_eq!(stats["totals"]["new"], 1);
+    assert_eq!(
+        stats["defining_packs"][0],
+        serde_json::json!({
+            "name": "packs/bar",
+            "violations": {
+                "recorded": 2,
+                "new": 1,
+                "by_type": {"dependency": {"recorded": 2, "new": 1}}
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_strict_mode() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/uses_strict_mode")
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 recorded, 0 new, 2 stale and 2 strict mode violation(s)",
+        ))
+        .stdout(predicate::str::contains(
+            "packs/foo  -      2         0    dependency, privacy",
+        ));
+
+    common::teardown();
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+// In simple_app, the reference to ::Bar on line 3 is both a dependency and a
+// privacy violation.
+fn fixture_with_foo(contents: &str) -> common::Fixture {
+    let fixture = common::Fixture::new("simple_app");
+    fs::write(fixture.path("packs/foo/app/services/foo.rb"), contents).unwrap();
+    fixture
+}
+
+#[test]
+fn test_disable_comment_suppresses_listed_violation_types(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    # pks:disable dependency -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains("Privacy violation: `::Bar`"))
+        .stdout(predicate::str::contains("Dependency violation").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_trailing_and_block_comments() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    ::Bar # pks:disable dependency,privacy -- Legacy\n  end\n\n  # pks:disable-block privacy, dependency -- Legacy\n  def calls_bar_again\n    ::Bar\n  end\n  # pks:enable-block\nend\n",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_suppressed_violations_are_not_recorded() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    # pks:disable dependency -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("update")
+        .assert()
+        .success();
+
+    let package_todo =
+        fs::read_to_string(fixture.path("packs/foo/package_todo.yml"))?;
+    assert!(package_todo.contains("privacy"));
+    assert!(!package_todo.contains("dependency"));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_comment_fails_the_check() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    # pks:disable dependency\n    ::Bar\n  end\nend\n",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("2 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "1 invalid `# pks:` comment(s) detected:\npacks/foo/app/services/foo.rb:3:4\nInvalid `# pks:disable` comment: a reason is required",
+        ))
+        .stdout(predicate::str::contains("parse error").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_comment_without_violations_fails_the_check(
+) -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  # pks:disabled privacy -- Typo\n  def call\n  end\nend\n",
+    );
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("-o")
+        .arg("json")
+        .output()?;
+    assert_eq!(output.status.code(), Some(1));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["summary"]["violation_count"], 0);
+    assert_eq!(json["summary"]["parse_error_count"], 0);
+    assert_eq!(json["summary"]["invalid_suppression_count"], 1);
+    assert_eq!(json["summary"]["success"], false);
+    assert_eq!(json["invalid_suppressions"][0]["line"], 2);
+    assert!(json["invalid_suppressions"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Unknown comment `# pks:disabled`"));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_comment_is_a_csv_row() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  # pks:disable privacy\n  def call\n  end\nend\n",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .arg("-o")
+        .arg("csv")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "invalid_suppression,false,packs/foo/app/services/foo.rb,,,,\"Invalid `# pks:disable` comment: a reason is required, e.g. `# pks:disable privacy -- <reason>`\",2,2\n",
+        ))
+        .stdout(predicate::str::contains("No violations detected!").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_erb_comments_are_reported_as_unsupported() -> Result<(), Box<dyn Error>>
+{
+    let fixture = common::Fixture::new("simple_app");
+    fs::create_dir_all(fixture.path("packs/foo/app/views"))?;
+    fs::write(
+        fixture.path("packs/foo/app/views/show.html.erb"),
+        "<div>\n  <%# pks:disable privacy -- Legacy %>\n  <%= Baz %>\n</div>\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "1 invalid `# pks:` comment(s) detected:\npacks/foo/app/views/show.html.erb:2:2\n`# pks:` comments are not supported in ERB files",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_violations_cannot_be_suppressed() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    # pks:disable dependency,privacy -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    );
+    fs::write(
+        fixture.path("packs/bar/package.yml"),
+        "enforce_privacy: strict\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "packs/foo cannot have privacy violations on packs/bar because strict mode is enabled",
+        ))
+        .stdout(predicate::str::contains("Dependency violation").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_changed_comment_is_honored_with_cache() -> Result<(), Box<dyn Error>> {
+    let fixture = fixture_with_foo(
+        "module Foo\n  def calls_bar\n    # pks:disable dependency -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    );
+    let packwerk_yml = fixture.path("packwerk.yml");
+    let contents = fs::read_to_string(&packwerk_yml)?;
+    fs::write(
+        &packwerk_yml,
+        contents.replace("cache: false", "cache: true"),
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Privacy violation"));
+
+    // Same references, different comment
+    fs::write(
+        fixture.path("packs/foo/app/services/foo.rb"),
+        "module Foo\n  def calls_bar\n    # pks:disable privacy -- Migrating to packs/bar\n    ::Bar\n  end\nend\n",
+    )?;
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Dependency violation"))
+        .stdout(predicate::str::contains("Privacy violation").not());
+
+    Ok(())
+}
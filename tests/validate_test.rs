@@ -31,6 +31,54 @@ packs/foo, packs/bar",
     Ok(())
 }
 
+#[test]
+fn test_validate_cycle_paths_with_reference_counts(
+) -> Result<(), Box<dyn Error>> {
+    let expected_message = String::from(
+        "
+packs/foo, packs/baz, packs/bar
+Cycles:
+  packs/bar → packs/foo → packs/bar
+  packs/bar → packs/baz → packs/foo → packs/bar
+Dependencies in these cycles, fewest references first:
+  packs/baz → packs/foo: 0 references, in 1 cycle
+  packs/bar → packs/baz: 1 reference, in 1 cycle
+  packs/bar → packs/foo: 1 reference, in 1 cycle
+  packs/foo → packs/bar: 2 references, in 2 cycles",
+    );
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_dependency_cycle_references")
+        .arg("validate")
+        .arg("--explain-cycles")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 validation error(s) detected:"))
+        .stdout(predicate::str::contains(expected_message));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_validate_cycle_paths_without_parsing() -> Result<(), Box<dyn Error>> {
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_dependency_cycle_references")
+        .arg("validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Cycles:\n  packs/bar → packs/foo → packs/bar\n  packs/bar → packs/baz → packs/foo → packs/bar\nRun `",
+        ))
+        .stdout(predicate::str::contains("validate --explain-cycles`"))
+        .stdout(predicate::str::contains("fewest references first").not());
+
+    common::teardown();
+    Ok(())
+}
+
 #[test]
 fn test_validate_layer() -> Result<(), Box<dyn Error>> {
     let expected_message_1 = String::from(
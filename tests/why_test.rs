@@ -0,0 +1,161 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_why_undeclared() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/foo")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "The dependency of packs/foo on packs/bar is not declared in its package.yml `dependencies`.
+
+1 reference(s) to 1 constant(s):
+
+::Bar (packs/bar/app/services/bar.rb)
+  packs/foo/app/services/foo.rb:3:4 [dependency violation, privacy violation]
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_why_with_repeated_references() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    fs::write(
+        fixture.path("packs/foo/app/services/foo.rb"),
+        "module Foo\n  def calls_bar\n    ::Bar\n    ::Bar\n    ::Bar\n  end\nend\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/foo")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 reference(s) to 1 constant(s):
+
+::Bar (packs/bar/app/services/bar.rb)
+  packs/foo/app/services/foo.rb:3:4 [dependency violation, privacy violation]
+  packs/foo/app/services/foo.rb:4:4 [dependency violation, privacy violation]
+  packs/foo/app/services/foo.rb:5:4 [dependency violation, privacy violation]
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_why_recorded() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/foo")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "is not declared, but is recorded in its package_todo.yml.",
+        ))
+        .stdout(predicate::str::contains(
+            "  packs/foo/app/services/foo.rb:3:4 [dependency violation (recorded)]\n  packs/foo/app/services/other_foo.rb:3:4 [dependency violation (recorded)]",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_why_with_only_privacy_recorded() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("contains_package_todo");
+    let package_todo_yml = fixture.path("packs/foo/package_todo.yml");
+    let package_todo = fs::read_to_string(&package_todo_yml)?;
+    fs::write(
+        &package_todo_yml,
+        package_todo.replace("    - dependency\n", "    - privacy\n"),
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/foo")
+        .arg("packs/bar")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "The dependency of packs/foo on packs/bar is not declared in its package.yml `dependencies`.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_why_declared_but_unused() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+    fs::write(
+        fixture.path("packs/bar/package.yml"),
+        "enforce_privacy: true\ndependencies:\n- packs/baz\n",
+    )?;
+
+    cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/bar")
+        .arg("packs/baz")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "The dependency of packs/bar on packs/baz is declared in its package.yml `dependencies`.
+
+No references from packs/bar to packs/baz.
+The dependency can be removed.
+",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_why_json() -> Result<(), Box<dyn Error>> {
+    let fixture = common::Fixture::new("simple_app");
+
+    let output = cargo_bin_cmd!("pks")
+        .arg("--project-root")
+        .arg(fixture.root())
+        .arg("why")
+        .arg("packs/foo")
+        .arg("packs/baz")
+        .arg("--format")
+        .arg("json")
+        .output()?;
+    assert!(output.status.success());
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["status"], "declared");
+    assert_eq!(json["constants"][0]["constant_name"], "::Baz");
+    assert_eq!(
+        json["constants"][0]["references"][0]["file"],
+        "packs/foo/app/services/foo.rb"
+    );
+
+    Ok(())
+}